@@ -4,8 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use dexa_limit_order::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, LastOrderIdResponse, OrderResponse, OrdersResponse,
-    QueryMsg,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, HodlerGainResponse, InstantiateMsg, LastOrderIdResponse,
+    OrderResponse, OrdersResponse, PairResponse, PairsResponse, PauseStatusResponse, QueryMsg, RequiredFeeResponse, RoutersResponse,
 };
 
 fn main() {
@@ -16,9 +16,16 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(OrderResponse), &out_dir);
     export_schema(&schema_for!(OrdersResponse), &out_dir);
     export_schema(&schema_for!(LastOrderIdResponse), &out_dir);
+    export_schema(&schema_for!(RequiredFeeResponse), &out_dir);
+    export_schema(&schema_for!(HodlerGainResponse), &out_dir);
+    export_schema(&schema_for!(PauseStatusResponse), &out_dir);
+    export_schema(&schema_for!(PairResponse), &out_dir);
+    export_schema(&schema_for!(PairsResponse), &out_dir);
+    export_schema(&schema_for!(RoutersResponse), &out_dir);
 }
@@ -1,14 +1,30 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Api, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdResult,
+};
+use cw20::Cw20ReceiveMsg;
 
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::order::{cancel_order, execute_order, submit_order};
-use crate::query::{query_config, query_last_order_id, query_order, query_orders};
-use crate::state::{Config, CONFIG, LAST_ORDER_ID};
+use crate::error::ContractError;
+use crate::msg::{Cw20HookMsg, Dex, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::order::{
+    add_fee, add_native_fee, cancel_grid, cancel_order, cancel_orders, execute_order, poke_order,
+    refund_expired, settle_order, submit_grid, submit_oco, submit_order, submit_order_cw20,
+    update_order,
+};
+use crate::query::{
+    query_config, query_grid_orders, query_hodler_gain, query_last_order_id, query_order, query_orders,
+    query_pair, query_pairs, query_pause_status, query_required_fee, query_routers,
+};
+use crate::state::{
+    read_pause, remove_pair, store_pair, Config, LegacyConfig, PairInfo, CONFIG, EXECUTE_ORDER_REPLY_ID, FEE_PERCENT_PRECISION,
+    LAST_ORDER_ID, LEGACY_CONFIG, ORDERS, PAIRS, PAUSE, PENDING_OWNER,
+    ROUTERS,
+};
 
-use cosmwasm_std::{Uint128, StdError};
-use terraswap::asset::{AssetInfo};
+use cosmwasm_std::Uint128;
+use terraswap::asset::{Asset, AssetInfo};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -16,80 +32,492 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
 
-    LAST_ORDER_ID.save(deps.storage, &0u64)?;
+    let config = Config {
+        owner: deps.api.addr_validate(&msg.owner)?.to_string(),
+        guardian: msg
+            .guardian
+            .map(|guardian| deps.api.addr_validate(&guardian).map(|addr| addr.to_string()))
+            .transpose()?,
+        fee_token: validate_fee_token(deps.api, msg.fee_token)?,
+        min_fee_amount: msg.min_fee_amount,
+        min_fee_percent: msg.min_fee_percent,
+        executor_fee_percent: msg.executor_fee_percent,
+        reserve_addr: deps.api.addr_validate(&msg.reserve_addr)?.to_string(),
+        terraswap_factory: deps.api.addr_validate(&msg.terraswap_factory)?.to_string(),
+    };
+    assert_config(&config)?;
 
-    update_config(deps,
-        _info,
-        true,
-        msg.fee_token,
-        msg.min_fee_amount,
-        msg.min_fee_percent,
-        msg.executor_fee_percent,
-        msg.reserve_addr
-    )?;
+    CONFIG.save(deps.storage, &config)?;
+    LAST_ORDER_ID.save(deps.storage, &0u64)?;
 
     Ok(Response::default())
 }
 
+fn assert_config(config: &Config) -> Result<(), ContractError> {
+    if config.min_fee_percent > Uint128::from(FEE_PERCENT_PRECISION) {
+        return Err(ContractError::InvalidConfig {
+            reason: format!("min_fee_percent exceeds {}", FEE_PERCENT_PRECISION),
+        });
+    }
+    if config.executor_fee_percent > Uint128::from(FEE_PERCENT_PRECISION) {
+        return Err(ContractError::InvalidConfig {
+            reason: format!("executor_fee_percent exceeds {}", FEE_PERCENT_PRECISION),
+        });
+    }
+
+    Ok(())
+}
+
+// cw20 fee tokens are contract addresses
+fn validate_fee_token(api: &dyn Api, fee_token: AssetInfo) -> StdResult<AssetInfo> {
+    Ok(match fee_token {
+        AssetInfo::Token { contract_addr } => AssetInfo::Token {
+            contract_addr: api.addr_validate(&contract_addr)?.to_string(),
+        },
+        AssetInfo::NativeToken { denom } => AssetInfo::NativeToken { denom },
+    })
+}
+
+// only given fields are updated, each change emits its old and new value
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    init: bool,
-    fee_token: AssetInfo,
-    min_fee_amount: Uint128, 
-    min_fee_percent: Uint128, 
-    executor_fee_percent: Uint128, 
-    reserve_addr: String
-) -> StdResult<Response> {
-
-    if !init {
-        // only allow to change config if executor is reserve_addr
-        let config: Config = CONFIG.load(deps.storage)?;
-        if info.sender.to_string() != config.reserve_addr {
-            return Err(StdError::generic_err("unauthorized, only reserve_addr owner can change config"));
+    guardian: Option<String>,
+    remove_guardian: Option<bool>,
+    fee_token: Option<AssetInfo>,
+    min_fee_amount: Option<Uint128>,
+    min_fee_percent: Option<Uint128>,
+    executor_fee_percent: Option<Uint128>,
+    reserve_addr: Option<String>,
+    terraswap_factory: Option<String>,
+) -> Result<Response, ContractError> {
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attrs = vec![attr("action", "update_config")];
+
+    let remove_guardian = remove_guardian.unwrap_or(false);
+    if remove_guardian && guardian.is_some() {
+        return Err(ContractError::InvalidConfig {
+            reason: "cannot set and remove the guardian at once".to_string(),
+        });
+    }
+    if let Some(guardian) = guardian {
+        let guardian = deps.api.addr_validate(&guardian)?.to_string();
+        attrs.push(attr("old_guardian", config.guardian.unwrap_or_else(|| "none".to_string())));
+        attrs.push(attr("new_guardian", guardian.clone()));
+        config.guardian = Some(guardian);
+    }
+    if remove_guardian {
+        attrs.push(attr("old_guardian", config.guardian.unwrap_or_else(|| "none".to_string())));
+        attrs.push(attr("new_guardian", "none"));
+        config.guardian = None;
+    }
+
+    if let Some(fee_token) = fee_token {
+        let fee_token = validate_fee_token(deps.api, fee_token)?;
+        // open orders hold fees paid in the current fee_token and are paid out or refunded in it
+        if fee_token != config.fee_token
+            && ORDERS.keys(deps.storage, None, None, Order::Ascending).next().is_some()
+        {
+            return Err(ContractError::InvalidConfig {
+                reason: "fee_token cannot change while orders are open".to_string(),
+            });
         }
+        attrs.push(attr("old_fee_token", config.fee_token.to_string()));
+        attrs.push(attr("new_fee_token", fee_token.to_string()));
+        config.fee_token = fee_token;
     }
 
-    let config = Config {
-        fee_token: fee_token,
-        min_fee_amount: min_fee_amount,
-        min_fee_percent: min_fee_percent,
-        executor_fee_percent: executor_fee_percent,
-        reserve_addr: reserve_addr
-    };
+    if let Some(min_fee_amount) = min_fee_amount {
+        attrs.push(attr("old_min_fee_amount", config.min_fee_amount));
+        attrs.push(attr("new_min_fee_amount", min_fee_amount));
+        config.min_fee_amount = min_fee_amount;
+    }
+
+    if let Some(min_fee_percent) = min_fee_percent {
+        attrs.push(attr("old_min_fee_percent", config.min_fee_percent));
+        attrs.push(attr("new_min_fee_percent", min_fee_percent));
+        config.min_fee_percent = min_fee_percent;
+    }
+
+    if let Some(executor_fee_percent) = executor_fee_percent {
+        attrs.push(attr("old_executor_fee_percent", config.executor_fee_percent));
+        attrs.push(attr("new_executor_fee_percent", executor_fee_percent));
+        config.executor_fee_percent = executor_fee_percent;
+    }
+
+    if let Some(reserve_addr) = reserve_addr {
+        let reserve_addr = deps.api.addr_validate(&reserve_addr)?.to_string();
+        attrs.push(attr("old_reserve_addr", config.reserve_addr));
+        attrs.push(attr("new_reserve_addr", reserve_addr.clone()));
+        config.reserve_addr = reserve_addr;
+    }
+
+    if let Some(terraswap_factory) = terraswap_factory {
+        let terraswap_factory = deps.api.addr_validate(&terraswap_factory)?.to_string();
+        attrs.push(attr("old_terraswap_factory", config.terraswap_factory));
+        attrs.push(attr("new_terraswap_factory", terraswap_factory.clone()));
+        config.terraswap_factory = terraswap_factory;
+    }
 
+    assert_config(&config)?;
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Owner or guardian updates the paused operations
+pub fn set_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    submit: Option<bool>,
+    execute: Option<bool>,
+    paused_dexes: Option<Vec<Dex>>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner && Some(info.sender.to_string()) != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut pause = read_pause(deps.storage)?;
+    if let Some(submit) = submit {
+        pause.submit = submit;
+    }
+    if let Some(execute) = execute {
+        pause.execute = execute;
+    }
+    if let Some(paused_dexes) = paused_dexes {
+        pause.paused_dexes = paused_dexes;
+    }
+    PAUSE.save(deps.storage, &pause)?;
+
+    let paused_dexes = pause
+        .paused_dexes
+        .iter()
+        .map(|dex| format!("{:?}", dex).to_lowercase())
+        .collect::<Vec<String>>()
+        .join(",");
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_pause"),
+        attr("sender", info.sender.to_string()),
+        attr("submit", pause.submit.to_string()),
+        attr("execute", pause.execute.to_string()),
+        attr("paused_dexes", paused_dexes),
+    ]))
+}
+
+/// Owner registers a pair after checking it trades asset_infos on dex
+pub fn register_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    dex: Dex,
+    asset_infos: [AssetInfo; 2],
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair_asset_infos = dex
+        .adapter()
+        .query_asset_infos(&deps.querier, &pair_addr)
+        .map_err(|_| ContractError::InvalidPair {
+            pair_addr: pair_addr.to_string(),
+            dex: format!("{:?}", dex),
+        })?;
+    if asset_infos[0] == asset_infos[1]
+        || !asset_infos.iter().all(|asset_info| pair_asset_infos.contains(asset_info))
+    {
+        return Err(ContractError::InvalidPair {
+            pair_addr: pair_addr.to_string(),
+            dex: format!("{:?}", dex),
+        });
+    }
+
+    store_pair(
+        deps.storage,
+        &PairInfo {
+            pair_addr: pair_addr.clone(),
+            dex,
+            asset_infos: asset_infos.clone(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_pair"),
+        attr("pair_addr", pair_addr.to_string()),
+        attr("dex", format!("{:?}", dex).to_lowercase()),
+        attr("asset_infos", format!("{},{}", asset_infos[0], asset_infos[1])),
+    ]))
+}
+
+pub fn deregister_pair(deps: DepsMut, info: MessageInfo, pair_addr: String) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair = PAIRS
+        .may_load(deps.storage, pair_addr.as_bytes())?
+        .ok_or(ContractError::PairNotRegistered {
+            pair_addr: pair_addr.to_string(),
+        })?;
+    remove_pair(deps.storage, &pair)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deregister_pair"),
+        attr("pair_addr", pair_addr.to_string()),
+    ]))
+}
+
+/// Owner registers a dex router, route orders swap through it
+pub fn register_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    router_addr: String,
+    dex: Dex,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let router_addr = deps.api.addr_validate(&router_addr)?;
+    ROUTERS.save(deps.storage, router_addr.as_bytes(), &dex)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_router"),
+        attr("router_addr", router_addr.to_string()),
+        attr("dex", format!("{:?}", dex).to_lowercase()),
+    ]))
+}
+
+pub fn deregister_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    router_addr: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let router_addr = deps.api.addr_validate(&router_addr)?;
+    let dex = ROUTERS
+        .may_load(deps.storage, router_addr.as_bytes())?
+        .ok_or(ContractError::RouterNotRegistered {
+            router_addr: router_addr.to_string(),
+        })?;
+    ROUTERS.remove(deps.storage, router_addr.as_bytes());
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deregister_router"),
+        attr("router_addr", router_addr.to_string()),
+        attr("dex", format!("{:?}", dex).to_lowercase()),
+    ]))
+}
+
+/// Owner proposes a new owner, who takes over with accept_ownership
+pub fn propose_new_owner(deps: DepsMut, info: MessageInfo, owner: String) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(deps.storage, &new_owner.to_string())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("new_owner", new_owner.to_string()),
+    ]))
+}
+
+pub fn drop_ownership_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "drop_ownership_proposal"))
+}
+
+/// The proposed owner becomes the owner
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    if info.sender != pending_owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let previous_owner = config.owner;
+    config.owner = pending_owner;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("previous_owner", previous_owner),
+        attr("new_owner", config.owner),
+    ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateConfig {
+            guardian,
+            remove_guardian,
             fee_token,
             min_fee_amount,
             min_fee_percent,
             executor_fee_percent,
-            reserve_addr
-        } => update_config(deps, info, false, fee_token, min_fee_amount, min_fee_percent, executor_fee_percent, reserve_addr),
+            reserve_addr,
+            terraswap_factory,
+        } => update_config(deps, info, guardian, remove_guardian, fee_token, min_fee_amount, min_fee_percent, executor_fee_percent, reserve_addr, terraswap_factory),
+        ExecuteMsg::SetPause {
+            submit,
+            execute,
+            paused_dexes,
+        } => set_pause(deps, info, submit, execute, paused_dexes),
+        ExecuteMsg::RegisterPair {
+            pair_addr,
+            dex,
+            asset_infos,
+        } => register_pair(deps, info, pair_addr, dex, asset_infos),
+        ExecuteMsg::DeregisterPair { pair_addr } => deregister_pair(deps, info, pair_addr),
+        ExecuteMsg::RegisterRouter { router_addr, dex } => register_router(deps, info, router_addr, dex),
+        ExecuteMsg::DeregisterRouter { router_addr } => deregister_router(deps, info, router_addr),
+        ExecuteMsg::ProposeNewOwner { owner } => propose_new_owner(deps, info, owner),
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
         ExecuteMsg::SubmitOrder {
             pair_addr,
+            dex,
+            offer_asset,
+            ask_asset,
+            fee_amount,
+            recurring,
+            expires_at,
+            kind,
+            route,
+            candidate_pools,
+        } => submit_order(
+            deps, env, info, pair_addr, dex, offer_asset, ask_asset, fee_amount, recurring, expires_at,
+            kind, route, candidate_pools,
+        ),
+        ExecuteMsg::SubmitGrid {
+            pair_addr,
+            dex,
+            offer_asset,
+            ask_asset_info,
+            lower_price,
+            upper_price,
+            levels,
+            fee_amount,
+            expires_at,
+        } => submit_grid(
+            deps, env, info, pair_addr, dex, offer_asset, ask_asset_info, lower_price, upper_price,
+            levels, fee_amount, expires_at,
+        ),
+        ExecuteMsg::SubmitOco {
+            pair_addr,
+            dex,
             offer_asset,
             ask_asset,
+            trigger_price,
+            min_return,
             fee_amount,
-            recurring
-        } => submit_order(deps, env, info, pair_addr, offer_asset, ask_asset, fee_amount, recurring),
+            expires_at,
+        } => submit_oco(
+            deps, env, info, pair_addr, dex, offer_asset, ask_asset, trigger_price, min_return,
+            fee_amount, expires_at,
+        ),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::AddFee { order_id } => add_native_fee(deps, info, order_id),
+        ExecuteMsg::UpdateOrder {
+            order_id,
+            ask_amount,
+            add_offer,
+            withdraw_offer,
+            add_fee,
+        } => update_order(deps, env, info, order_id, ask_amount, add_offer, withdraw_offer, add_fee),
         ExecuteMsg::CancelOrder { order_id } => cancel_order(deps, info, order_id),
-        ExecuteMsg::ExecuteOrder { order_id, dex } => execute_order(deps, env, info, order_id, dex),
+        ExecuteMsg::CancelOrders {
+            order_ids,
+            pair_addr,
+            limit,
+        } => cancel_orders(deps, info, order_ids, pair_addr, limit),
+        ExecuteMsg::CancelGrid { grid_id } => cancel_grid(deps, info, grid_id),
+        ExecuteMsg::RefundExpired { order_ids } => refund_expired(deps, env, order_ids),
+        ExecuteMsg::PokeOrder { order_id } => poke_order(deps, env, order_id),
+        ExecuteMsg::ExecuteOrder {
+            order_id,
+            amount,
+            split,
+        } => execute_order(deps, env, info, order_id, amount, split),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    // info.sender is the cw20 contract
+    let sent_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: info.sender.to_string(),
+        },
+        amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::SubmitOrder {
+            pair_addr,
+            dex,
+            ask_asset,
+            fee_amount,
+            recurring,
+            expires_at,
+            kind,
+            route,
+            candidate_pools,
+        } => submit_order_cw20(
+            deps, env, sender, sent_asset, pair_addr, dex, ask_asset, fee_amount, recurring, expires_at,
+            kind, route, candidate_pools,
+        ),
+        Cw20HookMsg::AddFee { order_id } => add_fee(deps, sender, order_id, sent_asset),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        EXECUTE_ORDER_REPLY_ID => settle_order(deps, env),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let res = match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Order { order_id } => to_binary(&query_order(deps, order_id)?),
         QueryMsg::Orders {
@@ -105,10 +533,51 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             order_by,
         )?),
         QueryMsg::LastOrderId {} => to_binary(&query_last_order_id(deps)?),
-    }
+        QueryMsg::RequiredFee { offer_asset } => {
+            to_binary(&query_required_fee(deps, offer_asset)?)
+        }
+        QueryMsg::GridOrders { grid_id } => to_binary(&query_grid_orders(deps, grid_id)?),
+        QueryMsg::HodlerGain { order_id } => to_binary(&query_hodler_gain(deps, order_id)?),
+        QueryMsg::PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        QueryMsg::Pair { pair_addr } => to_binary(&query_pair(deps, pair_addr)?),
+        QueryMsg::Pairs { start_after, limit } => to_binary(&query_pairs(deps, start_after, limit)?),
+        QueryMsg::Routers { start_after, limit } => to_binary(&query_routers(deps, start_after, limit)?),
+    }?;
+
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if CONFIG.load(deps.storage).is_ok() {
+        return Ok(Response::default());
+    }
+
+    // the stored config predates the owner
+    let legacy_config: LegacyConfig = LEGACY_CONFIG.load(deps.storage)?;
+    let owner = msg.owner.unwrap_or_else(|| legacy_config.reserve_addr.clone());
+    let terraswap_factory = msg
+        .terraswap_factory
+        .or(legacy_config.terraswap_factory)
+        .ok_or(ContractError::InvalidConfig {
+            reason: "terraswap_factory is required".to_string(),
+        })?;
+
+    let config = Config {
+        owner: deps.api.addr_validate(&owner)?.to_string(),
+        guardian: None,
+        fee_token: legacy_config.fee_token,
+        min_fee_amount: legacy_config.min_fee_amount,
+        min_fee_percent: legacy_config.min_fee_percent,
+        executor_fee_percent: legacy_config.executor_fee_percent,
+        reserve_addr: deps.api.addr_validate(&legacy_config.reserve_addr)?.to_string(),
+        terraswap_factory: deps.api.addr_validate(&terraswap_factory)?.to_string(),
+    };
+    assert_config(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("owner", config.owner),
+    ]))
 }
@@ -0,0 +1,525 @@
+// swap messages keep the explicit field names of the original execute_order
+#![allow(clippy::redundant_field_names)]
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, QuerierWrapper, QueryRequest, StdResult, Uint128,
+    WasmMsg, WasmQuery,
+};
+use serde::Serialize;
+use cw20::Cw20ExecuteMsg;
+use cw_asset::{Asset as CwAsset, AssetInfo as CwAssetInfo};
+use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use terraswap::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg,
+    SimulationResponse,
+};
+use terraswap::querier::simulate;
+use terraswap::router::{
+    Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg,
+    QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse, SwapOperation,
+};
+
+use prismswap::asset::PairInfo as PrismPairInfo;
+use prismswap::pair::{
+    ExecuteMsg as PrismPairExecuteMsg, QueryMsg as PrismPairQueryMsg,
+    SimulationResponse as PrismSimulationResponse,
+};
+use prismswap::querier::simulate as simulate_prism;
+use prismswap::router::{
+    Cw20HookMsg as PrismRouterCw20HookMsg, ExecuteMsg as PrismRouterExecuteMsg,
+    QueryMsg as PrismRouterQueryMsg, SwapOperation as PrismSwapOperation,
+};
+
+use crate::msg::Dex;
+
+/// Venue specific pair interface used by execute_order
+pub trait DexAdapter {
+    /// Asset infos traded by the pair, fails if pair_addr is not a pair of this venue
+    fn query_asset_infos(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+    ) -> StdResult<[AssetInfo; 2]>;
+
+    fn simulate(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+    ) -> StdResult<SimulationResponse>;
+
+    fn build_swap_msg(
+        &self,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    ) -> StdResult<CosmosMsg>;
+
+    /// Final return of the multi-hop route through the venue router
+    fn simulate_route(
+        &self,
+        querier: &QuerierWrapper,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+    ) -> StdResult<Uint128>;
+
+    fn build_route_swap_msg(
+        &self,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg>;
+}
+
+pub struct Terraswap;
+pub struct Astroport;
+pub struct Prism;
+
+impl Dex {
+    pub fn adapter(&self) -> &'static dyn DexAdapter {
+        match self {
+            Dex::Terraswap => &Terraswap,
+            Dex::Astroport => &Astroport,
+            Dex::Prism => &Prism,
+        }
+    }
+}
+
+impl DexAdapter for Terraswap {
+    fn query_asset_infos(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+    ) -> StdResult<[AssetInfo; 2]> {
+        let pair_info: PairInfo = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: pair_addr.to_string(),
+            msg: to_binary(&PairQueryMsg::Pair {})?,
+        }))?;
+
+        Ok(pair_info.asset_infos)
+    }
+
+    fn simulate(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+    ) -> StdResult<SimulationResponse> {
+        simulate(querier, pair_addr.clone(), offer_asset)
+    }
+
+    fn build_swap_msg(
+        &self,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    ) -> StdResult<CosmosMsg> {
+        match &offer_asset.info {
+            AssetInfo::Token { contract_addr } => {
+                cw20_swap_msg(contract_addr, pair_addr, offer_asset, belief_price, max_spread)
+            }
+            AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_addr.to_string(),
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount: offer_asset.amount,
+                }],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: offer_asset.clone(),
+                    belief_price: belief_price,
+                    max_spread: max_spread,
+                    to: None,
+                })?,
+            })),
+        }
+    }
+
+    fn simulate_route(
+        &self,
+        querier: &QuerierWrapper,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+    ) -> StdResult<Uint128> {
+        let res: SimulateSwapOperationsResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: router_addr.to_string(),
+                msg: to_binary(&RouterQueryMsg::SimulateSwapOperations {
+                    offer_amount: offer_asset.amount,
+                    operations: operations.to_vec(),
+                })?,
+            }))?;
+
+        Ok(res.amount)
+    }
+
+    fn build_route_swap_msg(
+        &self,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg> {
+        match &offer_asset.info {
+            AssetInfo::Token { contract_addr } => cw20_send_msg(
+                contract_addr,
+                router_addr,
+                offer_asset,
+                to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                    operations: operations.to_vec(),
+                    minimum_receive,
+                    to: None,
+                })?,
+            ),
+            AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: router_addr.to_string(),
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount: offer_asset.amount,
+                }],
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: operations.to_vec(),
+                    minimum_receive,
+                    to: None,
+                })?,
+            })),
+        }
+    }
+}
+
+// astroport and terraswap share the same pair interface
+impl DexAdapter for Astroport {
+    fn query_asset_infos(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+    ) -> StdResult<[AssetInfo; 2]> {
+        Terraswap.query_asset_infos(querier, pair_addr)
+    }
+
+    fn simulate(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+    ) -> StdResult<SimulationResponse> {
+        Terraswap.simulate(querier, pair_addr, offer_asset)
+    }
+
+    fn build_swap_msg(
+        &self,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    ) -> StdResult<CosmosMsg> {
+        Terraswap.build_swap_msg(pair_addr, offer_asset, belief_price, max_spread)
+    }
+
+    // astroport router names pool hops astro_swap instead of terra_swap
+    fn simulate_route(
+        &self,
+        querier: &QuerierWrapper,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+    ) -> StdResult<Uint128> {
+        let res: SimulateSwapOperationsResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: router_addr.to_string(),
+                msg: to_binary(&AstroportRouterQueryMsg::SimulateSwapOperations {
+                    offer_amount: offer_asset.amount,
+                    operations: to_astroport_operations(operations),
+                })?,
+            }))?;
+
+        Ok(res.amount)
+    }
+
+    fn build_route_swap_msg(
+        &self,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = AstroportRouterMsg::ExecuteSwapOperations {
+            operations: to_astroport_operations(operations),
+            minimum_receive,
+            to: None,
+        };
+
+        match &offer_asset.info {
+            AssetInfo::Token { contract_addr } => {
+                cw20_send_msg(contract_addr, router_addr, offer_asset, to_binary(&msg)?)
+            }
+            AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: router_addr.to_string(),
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount: offer_asset.amount,
+                }],
+                msg: to_binary(&msg)?,
+            })),
+        }
+    }
+}
+
+impl DexAdapter for Prism {
+    fn query_asset_infos(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+    ) -> StdResult<[AssetInfo; 2]> {
+        let pair_info: PrismPairInfo = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: pair_addr.to_string(),
+            msg: to_binary(&PrismPairQueryMsg::Pair {})?,
+        }))?;
+
+        Ok([
+            from_cw_asset_info(&pair_info.asset_infos[0]),
+            from_cw_asset_info(&pair_info.asset_infos[1]),
+        ])
+    }
+
+    fn simulate(
+        &self,
+        querier: &QuerierWrapper,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+    ) -> StdResult<SimulationResponse> {
+        // SimulationResponse is the same between terraswap and prismswap
+        let simul_res: PrismSimulationResponse =
+            simulate_prism(querier, pair_addr, &to_cw_asset(offer_asset))?;
+
+        Ok(SimulationResponse {
+            return_amount: simul_res.return_amount,
+            spread_amount: simul_res.spread_amount,
+            commission_amount: simul_res.commission_amount,
+        })
+    }
+
+    fn build_swap_msg(
+        &self,
+        pair_addr: &Addr,
+        offer_asset: &Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    ) -> StdResult<CosmosMsg> {
+        match &offer_asset.info {
+            // prism cw20 hook is the same with terraswap
+            AssetInfo::Token { contract_addr } => {
+                cw20_swap_msg(contract_addr, pair_addr, offer_asset, belief_price, max_spread)
+            }
+            AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_addr.to_string(),
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount: offer_asset.amount,
+                }],
+                msg: to_binary(&PrismPairExecuteMsg::Swap {
+                    offer_asset: to_cw_asset(offer_asset),
+                    belief_price: belief_price,
+                    max_spread: max_spread,
+                    to: None,
+                })?,
+            })),
+        }
+    }
+
+    fn simulate_route(
+        &self,
+        querier: &QuerierWrapper,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+    ) -> StdResult<Uint128> {
+        let res: SimulateSwapOperationsResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: router_addr.to_string(),
+                msg: to_binary(&PrismRouterQueryMsg::SimulateSwapOperations {
+                    offer_amount: offer_asset.amount,
+                    operations: to_prism_operations(operations),
+                })?,
+            }))?;
+
+        Ok(res.amount)
+    }
+
+    fn build_route_swap_msg(
+        &self,
+        router_addr: &Addr,
+        offer_asset: &Asset,
+        operations: &[SwapOperation],
+        minimum_receive: Option<Uint128>,
+    ) -> StdResult<CosmosMsg> {
+        match &offer_asset.info {
+            AssetInfo::Token { contract_addr } => cw20_send_msg(
+                contract_addr,
+                router_addr,
+                offer_asset,
+                to_binary(&PrismRouterCw20HookMsg::ExecuteSwapOperations {
+                    operations: to_prism_operations(operations),
+                    minimum_receive,
+                    to: None,
+                })?,
+            ),
+            AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: router_addr.to_string(),
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount: offer_asset.amount,
+                }],
+                msg: to_binary(&PrismRouterExecuteMsg::ExecuteSwapOperations {
+                    operations: to_prism_operations(operations),
+                    minimum_receive,
+                    to: None,
+                })?,
+            })),
+        }
+    }
+}
+
+fn cw20_swap_msg(
+    token_addr: &str,
+    pair_addr: &Addr,
+    offer_asset: &Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+) -> StdResult<CosmosMsg> {
+    cw20_send_msg(
+        token_addr,
+        pair_addr,
+        offer_asset,
+        to_binary(&PairCw20HookMsg::Swap {
+            to: None,
+            belief_price: belief_price,
+            max_spread: max_spread,
+        })?,
+    )
+}
+
+fn cw20_send_msg(
+    token_addr: &str,
+    contract_addr: &Addr,
+    offer_asset: &Asset,
+    msg: Binary,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token_addr.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: offer_asset.amount,
+            msg,
+        })?,
+    }))
+}
+
+// astroport router messages, the router hook message shares the execute message shape
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AstroportSwapOperation {
+    NativeSwap {
+        offer_denom: String,
+        ask_denom: String,
+    },
+    AstroSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AstroportRouterMsg {
+    ExecuteSwapOperations {
+        operations: Vec<AstroportSwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AstroportRouterQueryMsg {
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<AstroportSwapOperation>,
+    },
+}
+
+fn to_astroport_operations(operations: &[SwapOperation]) -> Vec<AstroportSwapOperation> {
+    operations
+        .iter()
+        .map(|operation| match operation {
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => AstroportSwapOperation::NativeSwap {
+                offer_denom: offer_denom.clone(),
+                ask_denom: ask_denom.clone(),
+            },
+            SwapOperation::TerraSwap {
+                offer_asset_info,
+                ask_asset_info,
+            } => AstroportSwapOperation::AstroSwap {
+                offer_asset_info: offer_asset_info.clone(),
+                ask_asset_info: ask_asset_info.clone(),
+            },
+        })
+        .collect()
+}
+
+fn to_prism_operations(operations: &[SwapOperation]) -> Vec<PrismSwapOperation> {
+    operations
+        .iter()
+        .map(|operation| match operation {
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => PrismSwapOperation::NativeSwap {
+                offer_denom: offer_denom.clone(),
+                ask_denom: ask_denom.clone(),
+            },
+            SwapOperation::TerraSwap {
+                offer_asset_info,
+                ask_asset_info,
+            } => PrismSwapOperation::PrismSwap {
+                offer_asset_info: to_cw_asset_info(offer_asset_info),
+                ask_asset_info: to_cw_asset_info(ask_asset_info),
+            },
+        })
+        .collect()
+}
+
+fn to_cw_asset(asset: &Asset) -> CwAsset {
+    CwAsset {
+        amount: asset.amount,
+        info: to_cw_asset_info(&asset.info),
+    }
+}
+
+fn to_cw_asset_info(info: &AssetInfo) -> CwAssetInfo {
+    match info {
+        AssetInfo::NativeToken { denom } => CwAssetInfo::Native(denom.clone()),
+        AssetInfo::Token { contract_addr } => {
+            CwAssetInfo::Cw20(Addr::unchecked(contract_addr.clone()))
+        }
+    }
+}
+
+fn from_cw_asset_info(info: &CwAssetInfo) -> AssetInfo {
+    match info {
+        CwAssetInfo::Native(denom) => AssetInfo::NativeToken {
+            denom: denom.clone(),
+        },
+        CwAssetInfo::Cw20(contract_addr) => AssetInfo::Token {
+            contract_addr: contract_addr.to_string(),
+        },
+    }
+}
@@ -0,0 +1,84 @@
+use cosmwasm_std::{Decimal, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("There is no ownership proposal")]
+    NoOwnershipProposal {},
+
+    #[error("Invalid config: {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("{operation} is paused")]
+    Paused { operation: String },
+
+    #[error("Order {id} not found")]
+    OrderNotFound { id: u64 },
+
+    #[error("Grid {id} has no open orders")]
+    GridNotFound { id: u64 },
+
+    #[error("Order is expired")]
+    Expired {},
+
+    #[error("Order {id} is not expired")]
+    NotExpired { id: u64 },
+
+    // simulated is the simulated return on execution and the received return on settlement
+    #[error("Insufficient return amount: expected {expected}, got {simulated}")]
+    InsufficientReturn { expected: Uint128, simulated: Uint128 },
+
+    #[error("Order is not triggered: price {price} is above {trigger_price}")]
+    NotTriggered { price: Decimal, trigger_price: Decimal },
+
+    #[error("Fee should be at least {min} (max of min_fee_amount and min_fee_percent of offer value)")]
+    FeeTooLow { min: Uint128 },
+
+    #[error("Order fee is not fully paid")]
+    FeeNotPaid {},
+
+    #[error("Fee should be paid in {fee_token}")]
+    InvalidFeeToken { fee_token: String },
+
+    #[error("There is no pair to price {asset} in {fee_token}")]
+    NoPricingPair { asset: String, fee_token: String },
+
+    #[error("Pair {pair_addr} does not trade the order assets on {dex}")]
+    InvalidPair { pair_addr: String, dex: String },
+
+    #[error("Pair {pair_addr} is not registered")]
+    PairNotRegistered { pair_addr: String },
+
+    #[error("Router {router_addr} is not registered")]
+    RouterNotRegistered { router_addr: String },
+
+    #[error("Invalid route: {reason}")]
+    InvalidRoute { reason: String },
+
+    #[error("No pool can simulate the order")]
+    NoSimulation {},
+
+    #[error("Invalid fill amount: {reason}")]
+    InvalidFillAmount { reason: String },
+
+    #[error("Invalid split: {reason}")]
+    InvalidSplit { reason: String },
+
+    #[error("Invalid order: {reason}")]
+    InvalidOrder { reason: String },
+
+    #[error("Too early, retry from {time}")]
+    TooEarly { time: u64 },
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+}
@@ -1,5 +1,10 @@
 pub mod contract;
+mod dex;
+pub mod error;
 pub mod msg;
 mod order;
 mod query;
 pub mod state;
+
+#[cfg(test)]
+mod testing;
@@ -1,46 +1,204 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
 use terraswap::asset::{Asset, AssetInfo};
+use terraswap::router::SwapOperation;
 
-use crate::state::{RecurringOrderOpt};
+use crate::state::{HodlerInfo, OrderKind, Pool, RecurringOrderOpt};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
+    // only the owner can change the config
+    pub owner: String,
+    // guardian can pause the contract along with the owner
+    pub guardian: Option<String>,
     pub fee_token: AssetInfo,
     pub min_fee_amount: Uint128,
     // 1000 = 1000/1000000=0.1%
     pub min_fee_percent: Uint128,
     pub executor_fee_percent: Uint128,
-    pub reserve_addr: String
+    pub reserve_addr: String,
+    // factory pair prices the offer asset in fee_token when no pair of them is registered
+    pub terraswap_factory: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    // update config, executor must be reserver_addr for security
+    // update config, executor must be owner for security
+    // reserve_addr only receives the reserve share of fees and excess
+    // omitted fields are left unchanged
     UpdateConfig {  
-        fee_token: AssetInfo,
-        min_fee_amount: Uint128,
-        // 1000 = 1000/1000000=0.1%
-        min_fee_percent: Uint128,
-        executor_fee_percent: Uint128,
-        reserve_addr: String
-    },
-    /// User submits a new order
+        guardian: Option<String>,
+        // clears the guardian, cannot be combined with guardian
+        remove_guardian: Option<bool>,
+        // can only change while no order is open
+        fee_token: Option<AssetInfo>,
+        min_fee_amount: Option<Uint128>,
+        // 1000 = 1000/1000000=0.1%, at most 1000000
+        min_fee_percent: Option<Uint128>,
+        executor_fee_percent: Option<Uint128>,
+        reserve_addr: Option<String>,
+        terraswap_factory: Option<String>,
+    },
+    /// Owner registers a pair orders can be submitted on, asset_infos should be the pair assets
+    RegisterPair {
+        pair_addr: String,
+        dex: Dex,
+        asset_infos: [AssetInfo; 2],
+    },
+    /// Owner removes a pair from the registry, its open orders no longer execute on it but can be cancelled
+    DeregisterPair { pair_addr: String },
+    /// Owner registers the router of a dex, route orders can only swap through registered routers
+    RegisterRouter { router_addr: String, dex: Dex },
+    /// Owner removes a router from the registry, its open orders no longer execute but can be cancelled
+    DeregisterRouter { router_addr: String },
+    /// Owner proposes a new owner, the change is done once the new owner accepts it
+    ProposeNewOwner { owner: String },
+    /// Owner withdraws the pending ownership proposal
+    DropOwnershipProposal {},
+    /// Proposed owner takes over the contract
+    AcceptOwnership {},
+    /// Owner or guardian pauses or resumes submissions, executions or executions on some dexes,
+    /// omitted fields are left unchanged. Pausing submissions also stops adding offer or fee to
+    /// open orders. Cancels, withdrawals and refunds are never paused
+    SetPause {
+        submit: Option<bool>,
+        execute: Option<bool>,
+        paused_dexes: Option<Vec<Dex>>,
+    },
+    /// User submits a new order on a registered pair
     /// Before, the user should increase allowance for the offer_asset (or send the native token) and the fee
+    /// Cw20 offer assets can be sent directly with Cw20HookMsg::SubmitOrder instead
     SubmitOrder {
         pair_addr: String,
+        dex: Dex,
         offer_asset: Asset,
         ask_asset: Asset,
         fee_amount: Uint128,
         recurring: Option<RecurringOrderOpt>,
+        expires_at: Option<Expiration>,
+        /// defaults to take_profit, for stop_loss, trailing_stop and dca only ask_asset.info is used
+        kind: Option<OrderKind>,
+        /// multi-hop route from offer_asset to ask_asset, pair_addr is then a registered router
+        route: Option<Vec<SwapOperation>>,
+        /// other pools of the same asset pair, the executor swaps on the best one
+        candidate_pools: Option<Vec<Pool>>,
+    },
+    /// User submits levels take-profit orders splitting offer_asset, asking prices
+    /// (ask per offer) evenly spaced from lower_price to upper_price
+    /// Funds are sent the same way as SubmitOrder, fee_amount is the fee of the whole grid
+    SubmitGrid {
+        pair_addr: String,
+        dex: Dex,
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        lower_price: Decimal,
+        upper_price: Decimal,
+        levels: u64,
+        fee_amount: Uint128,
+        expires_at: Option<Expiration>,
+    },
+    /// User submits a take-profit order asking ask_asset and a stop-loss order sharing
+    /// one offer_asset and one fee (one-cancels-other), funds are sent the same way as SubmitOrder
+    SubmitOco {
+        pair_addr: String,
+        dex: Dex,
+        offer_asset: Asset,
+        ask_asset: Asset,
+        trigger_price: Decimal,
+        min_return: Uint128,
+        fee_amount: Uint128,
+        expires_at: Option<Expiration>,
+    },
+    /// Cw20 entry point, see Cw20HookMsg
+    Receive(Cw20ReceiveMsg),
+    /// User tops up the fee of an existing order with the native fee token
+    AddFee { order_id: u64 },
+    /// User amends an existing order, omitted fields are left unchanged
+    /// ask_amount defaults to the current ask scaled to the new offer amount
+    /// add_offer and add_fee are sent the same way as SubmitOrder
+    UpdateOrder {
+        order_id: u64,
+        ask_amount: Option<Uint128>,
+        add_offer: Option<Uint128>,
+        withdraw_offer: Option<Uint128>,
+        add_fee: Option<Uint128>,
     },
     /// User operation to canel an existing order
     CancelOrder { order_id: u64 },
+    /// User operation to cancel several orders at once, refunds are sent once per asset
+    /// Without order_ids the first limit orders of the sender are cancelled
+    /// pair_addr restricts the cancelled orders to one pair
+    CancelOrders {
+        order_ids: Option<Vec<u64>>,
+        pair_addr: Option<String>,
+        limit: Option<u32>,
+    },
+    /// User operation to cancel the open orders of a grid
+    CancelGrid { grid_id: u64 },
+    /// Anyone can refund expired orders back to their bidders
+    RefundExpired { order_ids: Vec<u64> },
+    /// Anyone can raise the high_water_price of a trailing stop order to the current price,
+    /// at most once per POKE_INTERVAL seconds
+    PokeOrder { order_id: u64 },
     /// Executor operation to execute an existing order
-    ExecuteOrder { order_id: u64, dex: String },
+    /// amount is the part of the remaining offer to fill, defaults to all of it
+    /// split spreads the filled offer across pools of the order, amount is then the sum of the legs
+    ExecuteOrder {
+        order_id: u64,
+        amount: Option<Uint128>,
+        split: Option<Vec<SplitLeg>>,
+    },
+}
+
+/// Part of the filled offer swapped on one pool of the order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SplitLeg {
+    pub pool: Pool,
+    pub amount: Uint128,
+}
+
+/// Supported venues, see dex::DexAdapter
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Dex {
+    Terraswap,
+    Astroport,
+    Prism,
+}
+
+impl Default for Dex {
+    fn default() -> Self {
+        Dex::Terraswap
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum Cw20HookMsg {
+    /// User submits a new order offering the sent tokens
+    /// If fee_token is the sent token, the sent amount is offer amount + fee_amount,
+    /// otherwise the fee stays pending until paid with AddFee
+    SubmitOrder {
+        pair_addr: String,
+        dex: Dex,
+        ask_asset: Asset,
+        fee_amount: Uint128,
+        recurring: Option<RecurringOrderOpt>,
+        expires_at: Option<Expiration>,
+        /// defaults to take_profit, for stop_loss, trailing_stop and dca only ask_asset.info is used
+        kind: Option<OrderKind>,
+        /// multi-hop route from offer_asset to ask_asset, pair_addr is then a registered router
+        route: Option<Vec<SwapOperation>>,
+        /// other pools of the same asset pair, the executor swaps on the best one
+        candidate_pools: Option<Vec<Pool>>,
+    },
+    /// User tops up the fee of an existing order with the sent tokens
+    AddFee { order_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -57,15 +215,47 @@ pub enum QueryMsg {
         order_by: Option<OrderBy>,
     },
     LastOrderId {},
+    /// Minimum fee (in fee_token) required to submit an order offering offer_asset
+    RequiredFee {
+        offer_asset: Asset,
+    },
+    /// Open orders of a grid
+    GridOrders {
+        grid_id: u64,
+    },
+    /// Base asset gained by a submitted hodler order and its loops, also once the last loop is done
+    HodlerGain {
+        order_id: u64,
+    },
+    /// Paused operations, see ExecuteMsg::SetPause
+    PauseStatus {},
+    /// Registered pair
+    Pair {
+        pair_addr: String,
+    },
+    /// Registered pairs ordered by address
+    Pairs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Registered routers ordered by address
+    Routers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
+    pub guardian: Option<String>,
     pub fee_token: AssetInfo,
     pub min_fee_amount: Uint128,
     pub min_fee_percent: Uint128,
     pub executor_fee_percent: Uint128,
-    pub reserve_addr: String
+    pub reserve_addr: String,
+    pub terraswap_factory: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -73,10 +263,25 @@ pub struct OrderResponse {
     pub order_id: u64,
     pub bidder_addr: String,
     pub pair_addr: String,
+    pub dex: Dex,
     pub offer_asset: Asset,
     pub ask_asset: Asset,
     pub fee_amount: Uint128,
-    pub recurring: Option<RecurringOrderOpt>
+    pub pending_fee_amount: Uint128,
+    pub recurring: Option<RecurringOrderOpt>,
+    pub expires_at: Option<Expiration>,
+    pub kind: OrderKind,
+    pub filled_offer: Uint128,
+    pub filled_ask: Uint128,
+    pub route: Option<Vec<SwapOperation>>,
+    pub candidate_pools: Option<Vec<Pool>>,
+    pub last_fill_time: Option<u64>,
+    pub last_fill_price: Option<Decimal>,
+    pub hodler: Option<HodlerInfo>,
+    pub grid_id: Option<u64>,
+    pub linked_order_id: Option<u64>,
+    pub high_water_price: Option<Decimal>,
+    pub last_poke_time: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -89,6 +294,49 @@ pub struct LastOrderIdResponse {
     pub last_order_id: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RequiredFeeResponse {
+    pub fee_token: AssetInfo,
+    pub fee_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HodlerGainResponse {
+    pub order_id: u64,
+    pub base_asset: AssetInfo,
+    pub base_gain: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStatusResponse {
+    pub submit: bool,
+    pub execute: bool,
+    pub paused_dexes: Vec<Dex>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairResponse {
+    pub pair_addr: String,
+    pub dex: Dex,
+    pub asset_infos: [AssetInfo; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairsResponse {
+    pub pairs: Vec<PairResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouterResponse {
+    pub router_addr: String,
+    pub dex: Dex,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoutersResponse {
+    pub routers: Vec<RouterResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderBy {
@@ -96,5 +344,11 @@ pub enum OrderBy {
     Desc,
 }
 
+/// Only used when migrating from a version without owner
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// defaults to reserve_addr, the previous config admin
+    pub owner: Option<String>,
+    /// required if the stored config has none
+    pub terraswap_factory: Option<String>,
+}
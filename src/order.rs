@@ -1,42 +1,88 @@
-use crate::state::{remove_order, store_new_order, Config, OrderInfo, RecurringOrderOpt, CONFIG, ORDERS, POOL_PRISM};
+use crate::error::ContractError;
+use crate::msg::{Dex, SplitLeg};
+use crate::state::{
+    read_order, read_orders_by_grid, read_orders_by_user_pair, read_pair_by_assets, read_pause, remove_linked_order, remove_order,
+    store_new_order, Config, HodlerGain, HodlerInfo, OrderInfo, OrderKind, PendingExecution, Pool,
+    RecurringOrderOpt, CONFIG, EXECUTE_ORDER_REPLY_ID, FEE_PERCENT_PRECISION, LAST_GRID_ID, LAST_ORDER_ID, MAX_GRID_LEVELS,
+    HODLER_GAINS, ORDERS, PAIRS, PENDING_EXECUTION, POKE_INTERVAL, ROUTERS,
+};
 use cosmwasm_bignumber::{Decimal256};
 use cosmwasm_std::{
-    attr, to_binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128, Decimal, WasmMsg, QuerierWrapper, Addr
+    attr, to_binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128, Decimal, WasmMsg, QuerierWrapper, Addr, SubMsg, Storage, OverflowError,
+    OverflowOperation,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Expiration};
 use std::str::FromStr;
 //PairInfo
 use terraswap::asset::{Asset, AssetInfo};
-use terraswap::pair::{
-    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, SimulationResponse,
-};
-use cw_asset::{Asset as CwAsset, AssetInfo as CwAssetInfo};
+use terraswap::pair::SimulationResponse;
 
-//query_pair_info
-use terraswap::querier::{simulate};
-use prismswap::querier::{simulate as simulate_prism};
-use prismswap::pair::{ExecuteMsg as PrismPairExecuteMsg, SimulationResponse as PrismSimulationResponse};
+use terraswap::querier::query_pair_info;
+use terraswap::router::SwapOperation;
 
-pub fn submit_order(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    pair_addr: String,
-    offer_asset: Asset,
-    ask_asset: Asset,
-    fee_amount: Uint128,
-    recurring: Option<RecurringOrderOpt>,
-) -> StdResult<Response> {
-    let config: Config = CONFIG.load(deps.storage)?;
+/// Minimum fee for an order offering offer_asset: the larger of min_fee_amount and
+/// min_fee_percent of the offer value, priced in fee_token through a registered pair of any
+/// dex or else the factory pair.
+/// The value is the simulated return of the whole offer, so price impact makes it
+/// understate large offers on shallow pairs
+pub fn compute_required_fee(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    offer_asset: &Asset,
+) -> Result<Uint128, ContractError> {
+    if config.min_fee_percent.is_zero() {
+        return Ok(config.min_fee_amount);
+    }
+
+    let offer_value = if offer_asset.info == config.fee_token {
+        offer_asset.amount
+    } else {
+        let (dex, pair_addr) = pricing_pair(storage, querier, config, &offer_asset.info)?;
 
-    if fee_amount < config.min_fee_amount {
-        return Err(StdError::generic_err(format!(
-            "fee should be greater than {}",
-            config.min_fee_amount
-        )));
+        dex.adapter().simulate(querier, &pair_addr, offer_asset)?.return_amount
+    };
+
+    let percent_fee =
+        offer_value.multiply_ratio(config.min_fee_percent, Uint128::from(FEE_PERCENT_PRECISION));
+
+    Ok(std::cmp::max(config.min_fee_amount, percent_fee))
+}
+
+// registered pair of offer_info and fee_token, Terraswap first, else the factory pair
+fn pricing_pair(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    offer_info: &AssetInfo,
+) -> Result<(Dex, Addr), ContractError> {
+    let asset_infos = [offer_info.clone(), config.fee_token.clone()];
+    for dex in [Dex::Terraswap, Dex::Astroport, Dex::Prism].iter() {
+        if let Some(pair_addr) = read_pair_by_assets(storage, dex, &asset_infos)? {
+            return Ok((*dex, pair_addr));
+        }
     }
 
+    let pair_info = query_pair_info(querier, Addr::unchecked(config.terraswap_factory.clone()), &asset_infos)
+        .map_err(|_| {
+            ContractError::NoPricingPair {
+                asset: offer_info.to_string(),
+                fee_token: config.fee_token.to_string(),
+            }
+        })?;
+
+    Ok((Dex::Terraswap, Addr::unchecked(pair_info.contract_addr)))
+}
+
+// assert the sent native funds or pull the allowances of the offer asset and the fee
+fn collect_order_funds(
+    env: &Env,
+    info: &MessageInfo,
+    config: &Config,
+    offer_asset: &Asset,
+    fee_amount: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
     // fee_included meaning fee token is the same with offer_asset
     let fee_included = offer_asset.info == config.fee_token;
 
@@ -50,19 +96,11 @@ pub fn submit_order(
         offer_asset.clone()
     };
 
-    // check if the pair exists
-    // ignore this check for now, use the pair_addr from parameter instead
-    // let pair_info: PairInfo = query_pair_info(
-    //     &deps.querier,
-    //     config.terraswap_factory,
-    //     &[offer_asset.info.clone(), ask_asset.info.clone()],
-    // )
-    // .map_err(|_| StdError::generic_err("there is no terraswap pair for the 2 assets provided"))?;
-
     let mut messages: Vec<CosmosMsg> = vec![];
 
     match new_offer_asset.info.clone() {
-        AssetInfo::NativeToken { .. } => new_offer_asset.assert_sent_native_token_balance(&info)?,
+        AssetInfo::NativeToken { .. } => new_offer_asset.assert_sent_native_token_balance(info)?,
+        AssetInfo::Token { .. } if new_offer_asset.amount.is_zero() => {}
         AssetInfo::Token { contract_addr } => {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr,
@@ -80,7 +118,7 @@ pub fn submit_order(
     if !fee_included && fee_amount > Uint128::zero() {
         match config.fee_token.clone() {
             AssetInfo::NativeToken { .. } => {
-                Asset { amount: fee_amount, info : config.fee_token.clone()}.assert_sent_native_token_balance(&info)?
+                Asset { amount: fee_amount, info : config.fee_token.clone()}.assert_sent_native_token_balance(info)?
             },
             AssetInfo::Token { contract_addr } => {
                 messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -96,38 +134,878 @@ pub fn submit_order(
         }
     }
 
+    Ok(messages)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn submit_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    dex: Dex,
+    offer_asset: Asset,
+    ask_asset: Asset,
+    fee_amount: Uint128,
+    recurring: Option<RecurringOrderOpt>,
+    expires_at: Option<Expiration>,
+    kind: Option<OrderKind>,
+    route: Option<Vec<SwapOperation>>,
+    candidate_pools: Option<Vec<Pool>>,
+) -> Result<Response, ContractError> {
+    assert_submit_not_paused(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let messages = collect_order_funds(&env, &info, &config, &offer_asset, fee_amount)?;
+
+    let res = create_order(
+        deps,
+        &env,
+        &config,
+        info.sender,
+        pair_addr,
+        dex,
+        offer_asset,
+        ask_asset,
+        fee_amount,
+        Uint128::zero(),
+        recurring,
+        expires_at,
+        kind,
+        route,
+        candidate_pools,
+        None,
+        None,
+    )?;
+
+    Ok(res.add_messages(messages))
+}
+
+fn assert_submit_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if read_pause(storage)?.submit {
+        return Err(ContractError::Paused {
+            operation: "order submission".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Cw20 hook version of submit_order, the sent tokens are the offer asset (plus the fee
+/// if fee_token is the sent token). Otherwise the fee is left pending until paid with add_fee
+#[allow(clippy::too_many_arguments)]
+pub fn submit_order_cw20(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    pair_addr: String,
+    dex: Dex,
+    ask_asset: Asset,
+    fee_amount: Uint128,
+    recurring: Option<RecurringOrderOpt>,
+    expires_at: Option<Expiration>,
+    kind: Option<OrderKind>,
+    route: Option<Vec<SwapOperation>>,
+    candidate_pools: Option<Vec<Pool>>,
+) -> Result<Response, ContractError> {
+    assert_submit_not_paused(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // fee_included meaning fee token is the same with offer_asset
+    let fee_included = offer_asset.info == config.fee_token;
+
+    let (offer_asset, paid_fee_amount, pending_fee_amount) = if fee_included {
+        let amount = offer_asset.amount.checked_sub(fee_amount).map_err(|_| {
+            ContractError::InvalidOrder {
+                reason: "sent amount should cover the offer amount and the fee".to_string(),
+            }
+        })?;
+        (Asset { amount, ..offer_asset }, fee_amount, Uint128::zero())
+    } else {
+        (offer_asset, Uint128::zero(), fee_amount)
+    };
+
+    create_order(
+        deps,
+        &env,
+        &config,
+        sender,
+        pair_addr,
+        dex,
+        offer_asset,
+        ask_asset,
+        paid_fee_amount,
+        pending_fee_amount,
+        recurring,
+        expires_at,
+        kind,
+        route,
+        candidate_pools,
+        None,
+        None,
+    )
+}
+
+/// Split offer_asset into levels take-profit orders asking prices (ask per offer) evenly spaced
+/// from lower_price to upper_price, fee_amount is split the same way
+#[allow(clippy::too_many_arguments)]
+pub fn submit_grid(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    dex: Dex,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    lower_price: Decimal,
+    upper_price: Decimal,
+    levels: u64,
+    fee_amount: Uint128,
+    expires_at: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_submit_not_paused(deps.storage)?;
+    if !(2..=MAX_GRID_LEVELS).contains(&levels) {
+        return Err(ContractError::InvalidOrder {
+            reason: format!("levels should be between 2 and {}", MAX_GRID_LEVELS),
+        });
+    }
+    if lower_price.is_zero() || upper_price <= lower_price {
+        return Err(ContractError::InvalidOrder {
+            reason: "lower_price should be greater than 0 and less than upper_price".to_string(),
+        });
+    }
+    if offer_asset.amount < Uint128::from(levels) {
+        return Err(ContractError::InvalidOrder {
+            reason: "offer amount should cover every level".to_string(),
+        });
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let messages = collect_order_funds(&env, &info, &config, &offer_asset, fee_amount)?;
+
+    let grid_id = LAST_GRID_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LAST_GRID_ID.save(deps.storage, &grid_id)?;
+
+    // children are stored with consecutive order ids
+    let first_order_id = LAST_ORDER_ID.load(deps.storage)? + 1;
+
+    let level_offer_amount = offer_asset.amount.multiply_ratio(1u128, levels);
+    let level_fee_amount = fee_amount.multiply_ratio(1u128, levels);
+    for level in 0..levels {
+        // the last level takes the rounding leftovers
+        let is_last_level = level + 1 == levels;
+        let offer_amount = if is_last_level {
+            offer_asset.amount - level_offer_amount * Uint128::from(levels - 1)
+        } else {
+            level_offer_amount
+        };
+        let level_fee = if is_last_level {
+            fee_amount - level_fee_amount * Uint128::from(levels - 1)
+        } else {
+            level_fee_amount
+        };
+        let ask_amount = offer_amount * lower_price
+            + (offer_amount * (upper_price - lower_price)).multiply_ratio(level, levels - 1);
+
+        create_order(
+            deps.branch(),
+            &env,
+            &config,
+            info.sender.clone(),
+            pair_addr.clone(),
+            dex,
+            Asset {
+                amount: offer_amount,
+                info: offer_asset.info.clone(),
+            },
+            Asset {
+                amount: ask_amount,
+                info: ask_asset_info.clone(),
+            },
+            level_fee,
+            Uint128::zero(),
+            None,
+            expires_at,
+            None,
+            None,
+            None,
+            Some(grid_id),
+            None,
+        )?;
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "submit_grid"),
+        attr("grid_id", grid_id.to_string()),
+        attr("bidder_addr", info.sender.to_string()),
+        attr("offer_asset", offer_asset.to_string()),
+        attr(
+            "order_ids",
+            (first_order_id..first_order_id + levels)
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]))
+}
+
+/// Submit a take-profit and a stop-loss order sharing one escrowed offer_asset and one fee,
+/// filling or cancelling one of them removes the other
+#[allow(clippy::too_many_arguments)]
+pub fn submit_oco(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    dex: Dex,
+    offer_asset: Asset,
+    ask_asset: Asset,
+    trigger_price: Decimal,
+    min_return: Uint128,
+    fee_amount: Uint128,
+    expires_at: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_submit_not_paused(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let messages = collect_order_funds(&env, &info, &config, &offer_asset, fee_amount)?;
+
+    // siblings are stored with consecutive order ids
+    let take_profit_order_id = LAST_ORDER_ID.load(deps.storage)? + 1;
+    let stop_loss_order_id = take_profit_order_id + 1;
+
+    for (kind, linked_order_id) in [
+        (OrderKind::TakeProfit {}, stop_loss_order_id),
+        (OrderKind::StopLoss { trigger_price, min_return }, take_profit_order_id),
+    ] {
+        create_order(
+            deps.branch(),
+            &env,
+            &config,
+            info.sender.clone(),
+            pair_addr.clone(),
+            dex,
+            offer_asset.clone(),
+            ask_asset.clone(),
+            fee_amount,
+            Uint128::zero(),
+            None,
+            expires_at,
+            Some(kind),
+            None,
+            None,
+            None,
+            Some(linked_order_id),
+        )?;
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "submit_oco"),
+        attr("take_profit_order_id", take_profit_order_id.to_string()),
+        attr("stop_loss_order_id", stop_loss_order_id.to_string()),
+        attr("bidder_addr", info.sender.to_string()),
+        attr("offer_asset", offer_asset.to_string()),
+        attr("ask_asset", ask_asset.to_string()),
+    ]))
+}
+
+// check the pair is registered as trading the order assets on the given venue
+fn assert_pair_assets(
+    storage: &dyn Storage,
+    pair_addr: &Addr,
+    dex: &Dex,
+    offer_info: &AssetInfo,
+    ask_info: &AssetInfo,
+) -> Result<(), ContractError> {
+    let pair = PAIRS
+        .may_load(storage, pair_addr.as_bytes())?
+        .ok_or(ContractError::PairNotRegistered {
+            pair_addr: pair_addr.to_string(),
+        })?;
+    let asset_infos = pair.asset_infos;
+    if !(pair.dex == *dex
+        && asset_infos.contains(offer_info) && asset_infos.contains(ask_info) && offer_info != ask_info) {
+        return Err(ContractError::InvalidPair {
+            pair_addr: pair_addr.to_string(),
+            dex: format!("{:?}", dex),
+        });
+    }
+
+    Ok(())
+}
+
+// validate and store a new order whose offer asset and paid fee are already transferred
+#[allow(clippy::too_many_arguments)]
+fn create_order(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    bidder_addr: Addr,
+    pair_addr: String,
+    dex: Dex,
+    offer_asset: Asset,
+    ask_asset: Asset,
+    fee_amount: Uint128,
+    pending_fee_amount: Uint128,
+    recurring: Option<RecurringOrderOpt>,
+    expires_at: Option<Expiration>,
+    kind: Option<OrderKind>,
+    route: Option<Vec<SwapOperation>>,
+    candidate_pools: Option<Vec<Pool>>,
+    grid_id: Option<u64>,
+    linked_order_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    // a fee-only cw20 send leaves nothing to swap or refund
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidOrder {
+            reason: "offer_asset amount should be greater than 0".to_string(),
+        });
+    }
+
+    let kind = kind.unwrap_or(OrderKind::TakeProfit {});
+    if let OrderKind::StopLoss { trigger_price, min_return } = kind {
+        if trigger_price.is_zero() || min_return.is_zero() {
+            return Err(ContractError::InvalidOrder {
+                reason: "trigger_price and min_return should be greater than 0".to_string(),
+            });
+        }
+        if recurring.is_some() {
+            return Err(ContractError::InvalidOrder {
+                reason: "stop-loss orders cannot be recurring".to_string(),
+            });
+        }
+    }
+    if let OrderKind::TrailingStop { trail_percent, min_return } = kind {
+        if trail_percent.is_zero() || trail_percent >= Uint128::from(FEE_PERCENT_PRECISION) || min_return.is_zero() {
+            return Err(ContractError::InvalidOrder {
+                reason: "trail_percent should be between 0 and 1000000 and min_return greater than 0".to_string(),
+            });
+        }
+        if recurring.is_some() {
+            return Err(ContractError::InvalidOrder {
+                reason: "trailing stop orders cannot be recurring".to_string(),
+            });
+        }
+    }
+    if let OrderKind::Dca { interval, tranches, per_tranche_min_return } = kind {
+        if interval == 0 || tranches == 0 || per_tranche_min_return.is_zero() {
+            return Err(ContractError::InvalidOrder {
+                reason: "interval, tranches and per_tranche_min_return should be greater than 0".to_string(),
+            });
+        }
+        if offer_asset.amount < Uint128::from(tranches) {
+            return Err(ContractError::InvalidOrder {
+                reason: "offer amount should cover every tranche".to_string(),
+            });
+        }
+        if recurring.is_some() {
+            return Err(ContractError::InvalidOrder {
+                reason: "dca orders cannot be recurring".to_string(),
+            });
+        }
+    }
+    if let OrderKind::Surfing { band_percent, .. } = kind {
+        if band_percent.is_zero() || band_percent >= Uint128::from(FEE_PERCENT_PRECISION) {
+            return Err(ContractError::InvalidOrder {
+                reason: "band_percent should be between 0 and 1000000".to_string(),
+            });
+        }
+        if recurring.is_some() {
+            return Err(ContractError::InvalidOrder {
+                reason: "surfing orders cannot be recurring".to_string(),
+            });
+        }
+    }
+
+    if let Some(expires_at) = expires_at {
+        if expires_at.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    if let Some(route) = &route {
+        // pair_addr is the venue router, check the route swaps the order assets through it
+        // a router registered for another dex is not a router of the order
+        if ROUTERS.may_load(deps.storage, pair_addr.as_bytes())? != Some(dex) {
+            return Err(ContractError::RouterNotRegistered {
+                router_addr: pair_addr.to_string(),
+            });
+        }
+        assert_route(route, &offer_asset.info, &ask_asset.info)?;
+        assert_route_pairs(deps.storage, &dex, route)?;
+        dex.adapter()
+            .simulate_route(&deps.querier, &pair_addr, &offer_asset, route)
+            .map_err(|_| {
+                ContractError::InvalidRoute {
+                    reason: format!("{} cannot route the order on {:?}", pair_addr, dex),
+                }
+            })?;
+        if candidate_pools.is_some() {
+            return Err(ContractError::InvalidOrder {
+                reason: "route orders cannot have candidate pools".to_string(),
+            });
+        }
+    } else {
+        assert_pair_assets(deps.storage, &pair_addr, &dex, &offer_asset.info, &ask_asset.info)?;
+    }
+
+    // alternative pools of the same asset pair, the best one is picked on execution
+    let candidate_pools = candidate_pools
+        .map(|pools| {
+            pools
+                .into_iter()
+                .map(|pool| {
+                    let pair_addr = deps.api.addr_validate(pool.pair_addr.as_str())?;
+                    assert_pair_assets(deps.storage, &pair_addr, &pool.dex, &offer_asset.info, &ask_asset.info)?;
+                    Ok(Pool { pair_addr, dex: pool.dex })
+                })
+                .collect::<Result<Vec<Pool>, ContractError>>()
+        })
+        .transpose()?;
+
+    // loop orders divide by the belief prices
+    if let Some(recurring) = &recurring {
+        if recurring.belief_price.is_zero() || recurring.swapback_belief_price.is_zero() {
+            return Err(ContractError::InvalidOrder {
+                reason: "belief_price and swapback_belief_price should be greater than 0".to_string(),
+            });
+        }
+    }
+
+    // hodler orders sell offer_asset and must buy back more of it at swapback_belief_price
+    let hodler = match &recurring {
+        Some(recurring) if recurring.hodler => {
+            let buy_back_amount = ask_asset.amount
+                * Decimal::from(Decimal256::one() / Decimal256::from(recurring.swapback_belief_price));
+            if buy_back_amount <= offer_asset.amount {
+                return Err(ContractError::InvalidOrder {
+                    reason: "hodler orders should buy back more than offer_asset.amount".to_string(),
+                });
+            }
+            Some(HodlerInfo {
+                base: offer_asset.clone(),
+                origin_order_id: None,
+            })
+        }
+        _ => None,
+    };
+
+    let required_fee = compute_required_fee(deps.storage, &deps.querier, config, &offer_asset)?;
+    if fee_amount + pending_fee_amount < required_fee {
+        return Err(ContractError::FeeTooLow { min: required_fee });
+    }
+
     let mut new_order = OrderInfo {
         order_id: 0u64, // provisional
-        bidder_addr: deps.api.addr_validate(info.sender.as_str())?,
-        pair_addr: deps.api.addr_validate(&pair_addr)?,
+        bidder_addr: deps.api.addr_validate(bidder_addr.as_str())?,
+        pair_addr,
+        dex,
         offer_asset: offer_asset.clone(),
         ask_asset: ask_asset.clone(),
         fee_amount,
-        recurring: recurring.clone()
+        pending_fee_amount,
+        recurring,
+        expires_at,
+        kind,
+        filled_offer: Uint128::zero(),
+        filled_ask: Uint128::zero(),
+        route,
+        candidate_pools,
+        last_fill_time: None,
+        last_fill_price: None,
+        hodler,
+        grid_id,
+        linked_order_id,
+        high_water_price: None,
+        last_poke_time: None,
     };
+    // trailing stops start from the current price
+    if let OrderKind::TrailingStop { .. } = new_order.kind {
+        new_order.high_water_price = Some(query_order_price(&deps.querier, &new_order)?);
+    }
     store_new_order(deps.storage, &mut new_order)?;
+    if let Some(hodler) = &new_order.hodler {
+        HODLER_GAINS.save(
+            deps.storage,
+            &new_order.order_id.to_be_bytes(),
+            &HodlerGain {
+                base_asset: hodler.base.info.clone(),
+                base_gain: Uint128::zero(),
+            },
+        )?;
+    }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
+    Ok(Response::new().add_attributes(vec![
         attr("action", "submit_order"),
         attr("order_id", new_order.order_id.to_string()),
-        attr("bidder_addr", info.sender.to_string()),
+        attr("bidder_addr", bidder_addr.to_string()),
         attr("offer_asset", offer_asset.to_string()),
         attr("ask_asset", ask_asset.to_string()),
+        attr("pending_fee_amount", pending_fee_amount.to_string()),
     ]))
 }
 
-pub fn cancel_order(deps: DepsMut, info: MessageInfo, order_id: u64) -> StdResult<Response> {
+pub fn add_native_fee(deps: DepsMut, info: MessageInfo, order_id: u64) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let denom = match config.fee_token.clone() {
+        AssetInfo::NativeToken { denom } => denom,
+        AssetInfo::Token { .. } => {
+            return Err(ContractError::InvalidFeeToken {
+                fee_token: config.fee_token.to_string(),
+            })
+        }
+    };
+
+    let amount = info
+        .funds
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_else(Uint128::zero);
+
+    add_fee(deps, info.sender, order_id, Asset { info: config.fee_token, amount })
+}
+
+/// Top up the fee of an existing order, the fee_asset is already transferred
+pub fn add_fee(deps: DepsMut, sender: Addr, order_id: u64, fee_asset: Asset) -> Result<Response, ContractError> {
+    assert_submit_not_paused(deps.storage)?;
     let config: Config = CONFIG.load(deps.storage)?;
-    let order: OrderInfo = ORDERS.load(deps.storage, &order_id.to_be_bytes())?;
+    let mut order: OrderInfo = read_order(deps.storage, order_id)?;
+    if order.bidder_addr != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if fee_asset.info != config.fee_token {
+        return Err(ContractError::InvalidFeeToken {
+            fee_token: config.fee_token.to_string(),
+        });
+    }
+
+    if fee_asset.amount.is_zero() {
+        return Err(ContractError::InvalidOrder {
+            reason: "fee amount should be greater than 0".to_string(),
+        });
+    }
+
+    order.fee_amount += fee_asset.amount;
+    order.pending_fee_amount = order.pending_fee_amount.saturating_sub(fee_asset.amount);
+    ORDERS.save(deps.storage, &order_id.to_be_bytes(), &order)?;
+
+    sync_linked_fee(deps.storage, &order)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_fee"),
+        attr("order_id", order_id.to_string()),
+        attr("fee_amount", order.fee_amount.to_string()),
+        attr("pending_fee_amount", order.pending_fee_amount.to_string()),
+    ]))
+}
+
+// oco siblings share one fee
+fn sync_linked_fee(storage: &mut dyn Storage, order: &OrderInfo) -> Result<(), ContractError> {
+    if let Some(linked_order_id) = order.linked_order_id {
+        ORDERS.update(storage, &linked_order_id.to_be_bytes(), |linked_order| -> Result<_, ContractError> {
+            let mut linked_order = linked_order.ok_or(ContractError::OrderNotFound { id: linked_order_id })?;
+            linked_order.fee_amount = order.fee_amount;
+            Ok(linked_order)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Bidder amends an open order in place, added funds are sent the same way as SubmitOrder
+/// and withdrawn offer is refunded the same way as CancelOrder
+#[allow(clippy::too_many_arguments)]
+pub fn update_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: u64,
+    ask_amount: Option<Uint128>,
+    add_offer: Option<Uint128>,
+    withdraw_offer: Option<Uint128>,
+    add_fee: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut order: OrderInfo = read_order(deps.storage, order_id)?;
     if order.bidder_addr != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+    if order.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let add_offer = add_offer.unwrap_or_default();
+    let withdraw_offer = withdraw_offer.unwrap_or_default();
+    let add_fee = add_fee.unwrap_or_default();
+    if !add_offer.is_zero() && !withdraw_offer.is_zero() {
+        return Err(ContractError::InvalidOrder {
+            reason: "cannot add and withdraw offer at once".to_string(),
+        });
+    }
+    // no new funds come in while submissions are paused, withdrawing still works
+    if !add_offer.is_zero() || !add_fee.is_zero() {
+        assert_submit_not_paused(deps.storage)?;
+    }
+    if (!add_offer.is_zero() || !withdraw_offer.is_zero()) && order.linked_order_id.is_some() {
+        return Err(ContractError::InvalidOrder {
+            reason: "offer of oco orders cannot be changed".to_string(),
+        });
+    }
+    if withdraw_offer >= order.offer_asset.amount {
+        return Err(ContractError::InvalidOrder {
+            reason: "withdraw_offer should be less than the offer amount, cancel the order instead".to_string(),
+        });
     }
 
-    // refund offer asset
-    let mut messages: Vec<CosmosMsg> = vec![order
-        .offer_asset
-        .clone()
-        .into_msg(&deps.querier, order.bidder_addr.clone())?];
+    let mut messages = collect_order_funds(
+        &env,
+        &info,
+        &config,
+        &Asset {
+            amount: add_offer,
+            info: order.offer_asset.info.clone(),
+        },
+        add_fee,
+    )?;
+    if !withdraw_offer.is_zero() {
+        let withdrawn_asset = Asset {
+            amount: withdraw_offer,
+            info: order.offer_asset.info.clone(),
+        };
+        messages.push(withdrawn_asset.into_msg(&deps.querier, order.bidder_addr.clone())?);
+    }
+
+    // amounts tied to the offer follow it unless a new ask_amount is given
+    let old_offer_amount = order.offer_asset.amount;
+    let new_offer_amount = old_offer_amount + add_offer - withdraw_offer;
+    order.offer_asset.amount = new_offer_amount;
+    order.ask_asset.amount = ask_amount.unwrap_or_else(|| {
+        order
+            .ask_asset
+            .amount
+            .multiply_ratio(new_offer_amount, old_offer_amount)
+    });
+    match order.kind {
+        OrderKind::StopLoss { ref mut min_return, .. }
+        | OrderKind::TrailingStop { ref mut min_return, .. } => {
+            *min_return = min_return.multiply_ratio(new_offer_amount, old_offer_amount)
+        }
+        OrderKind::Dca { tranches, .. } if new_offer_amount < Uint128::from(tranches) => {
+            return Err(ContractError::InvalidOrder {
+                reason: "offer amount should cover every tranche".to_string(),
+            });
+        }
+        _ => {}
+    }
+    if let Some(hodler) = &mut order.hodler {
+        if hodler.base.info == order.offer_asset.info {
+            hodler.base.amount = new_offer_amount;
+        }
+    }
+    if order.fill_min_return(new_offer_amount).is_zero() {
+        return Err(ContractError::InvalidOrder {
+            reason: "ask amount should be greater than 0".to_string(),
+        });
+    }
+
+    order.fee_amount += add_fee;
+    order.pending_fee_amount = order.pending_fee_amount.saturating_sub(add_fee);
+    let required_fee = compute_required_fee(deps.storage, &deps.querier, &config, &order.offer_asset)?;
+    if order.fee_amount + order.pending_fee_amount < required_fee {
+        return Err(ContractError::FeeTooLow { min: required_fee });
+    }
+    ORDERS.save(deps.storage, &order_id.to_be_bytes(), &order)?;
+
+    sync_linked_fee(deps.storage, &order)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "update_order"),
+        attr("order_id", order_id.to_string()),
+        attr("offer_asset", order.offer_asset.to_string()),
+        attr("ask_asset", order.ask_asset.to_string()),
+        attr("fee_amount", order.fee_amount.to_string()),
+        attr("pending_fee_amount", order.pending_fee_amount.to_string()),
+    ]))
+}
+
+pub fn cancel_order(deps: DepsMut, info: MessageInfo, order_id: u64) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let order: OrderInfo = read_order(deps.storage, order_id)?;
+    if order.bidder_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (messages, refund_fee_asset) = refund_order_msgs(&deps.querier, &config, &order)?;
+
+    remove_order(deps.storage, &order);
+    let linked_order_id = remove_linked_order(deps.storage, &order)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "cancel_order"),
+        attr("order_id", order_id.to_string()),
+        attr("linked_order_id", linked_order_id.map(|id| id.to_string()).unwrap_or_default()),
+        attr("refunded_asset", order.offer_asset.to_string()),
+        attr("refunded_fee", refund_fee_asset.to_string()),
+    ]))
+}
+
+/// Cancel every remaining order of a grid
+pub fn cancel_grid(deps: DepsMut, info: MessageInfo, grid_id: u64) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let orders = read_orders_by_grid(deps.storage, grid_id)?;
+    if orders.is_empty() {
+        return Err(ContractError::GridNotFound { id: grid_id });
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for order in orders.iter() {
+        if order.bidder_addr != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let (refund_messages, _) = refund_order_msgs(&deps.querier, &config, order)?;
+        messages.extend(refund_messages);
+
+        remove_order(deps.storage, order);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "cancel_grid"),
+        attr("grid_id", grid_id.to_string()),
+        attr(
+            "order_ids",
+            orders
+                .iter()
+                .map(|order| order.order_id.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]))
+}
+
+/// Cancel the given orders of the sender, or its first limit orders if order_ids is omitted,
+/// only the ones on pair_addr if given. Refunds are sent once per asset
+pub fn cancel_orders(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_ids: Option<Vec<u64>>,
+    pair_addr: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_addr = pair_addr
+        .map(|pair_addr| deps.api.addr_validate(&pair_addr))
+        .transpose()?;
+
+    let orders: Vec<OrderInfo> = if let Some(order_ids) = order_ids {
+        order_ids
+            .iter()
+            .map(|order_id| read_order(deps.storage, *order_id))
+            .filter(|order| match (order, &pair_addr) {
+                (Ok(order), Some(pair_addr)) => order.pair_addr == *pair_addr,
+                _ => true,
+            })
+            .collect::<Result<_, ContractError>>()?
+    } else {
+        read_orders_by_user_pair(deps.storage, &info.sender, pair_addr.as_ref(), limit)?
+    };
+
+    let mut refund_assets: Vec<Asset> = vec![];
+    let mut cancelled_order_ids: Vec<u64> = vec![];
+    for order in orders.iter() {
+        if order.bidder_addr != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        // oco siblings are refunded once
+        if cancelled_order_ids.contains(&order.order_id) {
+            continue;
+        }
+
+        for asset in [
+            order.offer_asset.clone(),
+            Asset {
+                info: config.fee_token.clone(),
+                amount: order.fee_amount,
+            },
+        ] {
+            match refund_assets.iter_mut().find(|refund_asset| refund_asset.info == asset.info) {
+                Some(refund_asset) => refund_asset.amount += asset.amount,
+                None => refund_assets.push(asset),
+            }
+        }
+
+        remove_order(deps.storage, order);
+        cancelled_order_ids.push(order.order_id);
+        cancelled_order_ids.extend(remove_linked_order(deps.storage, order)?);
+    }
+
+    let messages = refund_assets
+        .into_iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.into_msg(&deps.querier, info.sender.clone()))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "cancel_orders"),
+        attr(
+            "order_ids",
+            cancelled_order_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(","),
+        ),
+    ]))
+}
+
+/// Anyone can refund expired orders back to their bidders
+pub fn refund_expired(deps: DepsMut, env: Env, order_ids: Vec<u64>) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    // refunded orders and their oco siblings, a repeated id is refunded once
+    let mut processed_order_ids: Vec<u64> = vec![];
+    for order_id in order_ids.iter() {
+        if processed_order_ids.contains(order_id) {
+            continue;
+        }
+
+        let order: OrderInfo = read_order(deps.storage, *order_id)?;
+        if !order.is_expired(&env.block) {
+            return Err(ContractError::NotExpired { id: *order_id });
+        }
+
+        let (refund_messages, _) = refund_order_msgs(&deps.querier, &config, &order)?;
+        messages.extend(refund_messages);
+
+        remove_order(deps.storage, &order);
+        processed_order_ids.push(*order_id);
+        processed_order_ids.extend(remove_linked_order(deps.storage, &order)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "refund_expired"),
+        attr(
+            "order_ids",
+            order_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(","),
+        ),
+    ]))
+}
+
+// messages refunding the offer asset and the paid fee of an order to its bidder
+fn refund_order_msgs(
+    querier: &QuerierWrapper,
+    config: &Config,
+    order: &OrderInfo,
+) -> Result<(Vec<CosmosMsg>, Asset), ContractError> {
+    // refund offer asset if any, cw20 tokens reject zero transfers
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if order.offer_asset.amount > Uint128::zero() {
+        messages.push(
+            order
+                .offer_asset
+                .clone()
+                .into_msg(querier, order.bidder_addr.clone())?,
+        );
+    }
 
     // refund fee if any
     let refund_fee_asset = Asset {
@@ -138,136 +1016,602 @@ pub fn cancel_order(deps: DepsMut, info: MessageInfo, order_id: u64) -> StdResul
         messages.push(
             refund_fee_asset
                 .clone()
-                .into_msg(&deps.querier, order.bidder_addr.clone())?,
+                .into_msg(querier, order.bidder_addr.clone())?,
         );
     }
 
-    remove_order(deps.storage, &order);
+    Ok((messages, refund_fee_asset))
+}
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "cancel_order"),
-        attr("order_id", order_id.to_string()),
-        attr("refunded_asset", order.offer_asset.to_string()),
-        attr("refunded_fee", refund_fee_asset.to_string()),
-    ]))
+// offer and ask asset infos of a route hop
+fn operation_asset_infos(operation: &SwapOperation) -> (AssetInfo, AssetInfo) {
+    match operation {
+        SwapOperation::NativeSwap {
+            offer_denom,
+            ask_denom,
+        } => (
+            AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            AssetInfo::NativeToken {
+                denom: ask_denom.clone(),
+            },
+        ),
+        SwapOperation::TerraSwap {
+            offer_asset_info,
+            ask_asset_info,
+        } => (offer_asset_info.clone(), ask_asset_info.clone()),
+    }
 }
 
-fn simulate_prism_adapter(querier: &QuerierWrapper,
-    pair_contract: &Addr,
-    offer_asset: &Asset) -> StdResult<SimulationResponse> {
-    let prism_offer_asset = CwAsset {
-            amount: offer_asset.amount,
-            info: match &offer_asset.info {
-                AssetInfo::NativeToken { denom } => CwAssetInfo::Native(denom.clone()),
-                AssetInfo::Token { contract_addr } => CwAssetInfo::Cw20(Addr::unchecked(contract_addr.clone()))
-            }
-    };
+// check the route hops are chained from offer_info to ask_info
+fn assert_route(route: &[SwapOperation], offer_info: &AssetInfo, ask_info: &AssetInfo) -> Result<(), ContractError> {
+    if route.is_empty() {
+        return Err(ContractError::InvalidRoute {
+            reason: "route should have at least one operation".to_string(),
+        });
+    }
 
-    // SimulationResponse is the same between terraswap and prismswap
-    let simul_res: PrismSimulationResponse =
-        simulate_prism(querier, pair_contract, &prism_offer_asset)?;
+    let mut current_info = offer_info.clone();
+    for operation in route.iter() {
+        let (operation_offer_info, operation_ask_info) = operation_asset_infos(operation);
+        if operation_offer_info != current_info {
+            return Err(ContractError::InvalidRoute {
+                reason: format!(
+                    "operation offers {} but the previous hop returns {}",
+                    operation_offer_info, current_info
+                ),
+            });
+        }
+        current_info = operation_ask_info;
+    }
 
-    Ok(SimulationResponse {
-        return_amount: simul_res.return_amount,
-        spread_amount: simul_res.spread_amount,
-        commission_amount: simul_res.commission_amount
-    })
+    if current_info != *ask_info {
+        return Err(ContractError::InvalidRoute {
+            reason: format!("route returns {} instead of {}", current_info, ask_info),
+        });
+    }
+
+    Ok(())
 }
 
-fn simulate_multipools(querier: &QuerierWrapper,
-    dex: String,
-    pair_contract: Addr,
-    offer_asset: &Asset) -> StdResult<SimulationResponse> {
-    if dex == POOL_PRISM {
-        simulate_prism_adapter(querier, &pair_contract, offer_asset)
-    } else {
-        // astroport and terraswap share the same interface
-        simulate(querier, pair_contract, offer_asset)
+// check every pair hop of the route has a registered pair on the venue,
+// native swaps go through the market module
+fn assert_route_pairs(storage: &dyn Storage, dex: &Dex, route: &[SwapOperation]) -> Result<(), ContractError> {
+    for operation in route.iter() {
+        if let SwapOperation::TerraSwap { .. } = operation {
+            let (offer_info, ask_info) = operation_asset_infos(operation);
+            if read_pair_by_assets(storage, dex, &[offer_info.clone(), ask_info.clone()])?.is_none() {
+                return Err(ContractError::InvalidRoute {
+                    reason: format!("no registered pair swaps {} to {} on {:?}", offer_info, ask_info, dex),
+                });
+            }
+        }
     }
+
+    Ok(())
 }
 
-pub fn execute_order(deps: DepsMut, _env: Env, _info: MessageInfo, order_id: u64, dex: String) -> StdResult<Response> {
-    let config: Config = CONFIG.load(deps.storage)?;
-    let order: OrderInfo = ORDERS.load(deps.storage, &order_id.to_be_bytes())?;
-    
-    // deduct tax if native
-    let offer_asset = if order.offer_asset.is_native_token() {
-        let amount = order.offer_asset.deduct_tax(&deps.querier)?.amount;
+// route in the opposite direction, used by the swap back of recurring orders
+fn reverse_route(route: Vec<SwapOperation>) -> Vec<SwapOperation> {
+    route
+        .into_iter()
+        .rev()
+        .map(|operation| match operation {
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => SwapOperation::NativeSwap {
+                offer_denom: ask_denom,
+                ask_denom: offer_denom,
+            },
+            SwapOperation::TerraSwap {
+                offer_asset_info,
+                ask_asset_info,
+            } => SwapOperation::TerraSwap {
+                offer_asset_info: ask_asset_info,
+                ask_asset_info: offer_asset_info,
+            },
+        })
+        .collect()
+}
 
-        Asset {
-            amount,
-            ..order.offer_asset.clone()
+fn simulate_multipools(
+    querier: &QuerierWrapper,
+    dex: &Dex,
+    pair_contract: &Addr,
+    offer_asset: &Asset,
+) -> StdResult<SimulationResponse> {
+    dex.adapter().simulate(querier, pair_contract, offer_asset)
+}
+
+// simulate the offer on every pool and pick the one with the highest return,
+// pools whose simulation fails are skipped
+fn best_pool(
+    querier: &QuerierWrapper,
+    pools: &[Pool],
+    offer_asset: &Asset,
+) -> Result<(Pool, SimulationResponse), ContractError> {
+    let mut best: Option<(Pool, SimulationResponse)> = None;
+    for pool in pools.iter() {
+        let simul_res = match simulate_multipools(querier, &pool.dex, &pool.pair_addr, offer_asset) {
+            Ok(simul_res) => simul_res,
+            Err(_) => continue,
+        };
+
+        let is_better = best
+            .as_ref()
+            .map_or(true, |(_, best_res)| simul_res.return_amount > best_res.return_amount);
+        if is_better {
+            best = Some((pool.clone(), simul_res));
         }
+    }
+
+    best.ok_or(ContractError::NoSimulation {})
+}
+
+// split amount into (executor share, reserve share) according to config.executor_fee_percent
+fn split_executor_share(config: &Config, amount: Uint128) -> (Uint128, Uint128) {
+    let executor_amount =
+        amount.multiply_ratio(config.executor_fee_percent, Uint128::from(FEE_PERCENT_PRECISION));
+    (executor_amount, amount - executor_amount)
+}
+
+// current price (return per offer) of the remaining offer along the route or on the best pool
+fn query_order_price(querier: &QuerierWrapper, order: &OrderInfo) -> Result<Decimal, ContractError> {
+    let offer_asset = deduct_offer_tax(querier, order.offer_asset.clone())?;
+    let return_amount = if let Some(route) = &order.route {
+        order
+            .dex
+            .adapter()
+            .simulate_route(querier, &order.pair_addr, &offer_asset, route)?
     } else {
-            order.offer_asset.clone()
+        best_pool(querier, &order.pools(), &offer_asset)?.1.return_amount
     };
 
-    let simul_res: SimulationResponse =
-        simulate_multipools(&deps.querier, dex.clone(), order.pair_addr.clone(), &offer_asset)?;
+    Ok(Decimal::from_ratio(return_amount, offer_asset.amount))
+}
 
-    if simul_res.return_amount < order.ask_asset.amount {
-        return Err(StdError::generic_err("insufficient return amount"));
+/// Permissionless, raises the high-water mark of a trailing stop order to the current price
+pub fn poke_order(deps: DepsMut, env: Env, order_id: u64) -> Result<Response, ContractError> {
+    let mut order: OrderInfo = read_order(deps.storage, order_id)?;
+    if !matches!(order.kind, OrderKind::TrailingStop { .. }) {
+        return Err(ContractError::InvalidOrder {
+            reason: "only trailing stop orders can be poked".to_string(),
+        });
+    }
+    if let Some(last_poke_time) = order.last_poke_time {
+        if env.block.time.seconds() < last_poke_time + POKE_INTERVAL {
+            return Err(ContractError::TooEarly {
+                time: last_poke_time + POKE_INTERVAL,
+            });
+        }
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let price = query_order_price(&deps.querier, &order)?;
+    if order.high_water_price.map_or(true, |high_water_price| price > high_water_price) {
+        order.high_water_price = Some(price);
+    }
+    order.last_poke_time = Some(env.block.time.seconds());
+    ORDERS.save(deps.storage, &order_id.to_be_bytes(), &order)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "poke_order"),
+        attr("order_id", order_id.to_string()),
+        attr("price", price.to_string()),
+        attr("high_water_price", order.high_water_price.unwrap_or_default().to_string()),
+    ]))
+}
+
+// take-profit orders fill once the simulated return reaches ask_asset.amount, stop-loss orders
+// once the price (return per offer) drops to trigger_price and the return still covers min_return,
+// trailing stop orders once the price drops trail_percent below high_water_price
+fn assert_order_triggered(
+    order: &OrderInfo,
+    offer_asset: &Asset,
+    min_return: Uint128,
+    simul_res: &SimulationResponse,
+) -> Result<(), ContractError> {
+    if let OrderKind::StopLoss { trigger_price, .. } = order.kind {
+        let price = Decimal::from_ratio(simul_res.return_amount, offer_asset.amount);
+        if price > trigger_price {
+            return Err(ContractError::NotTriggered { price, trigger_price });
+        }
+    }
+
+    if let OrderKind::TrailingStop { trail_percent, .. } = order.kind {
+        let high_water_price = order.high_water_price.unwrap_or_default();
+        let trigger_return = (offer_asset.amount * high_water_price).multiply_ratio(
+            Uint128::from(FEE_PERCENT_PRECISION) - trail_percent,
+            Uint128::from(FEE_PERCENT_PRECISION),
+        );
+        if simul_res.return_amount > trigger_return {
+            return Err(ContractError::NotTriggered {
+                price: Decimal::from_ratio(simul_res.return_amount, offer_asset.amount),
+                trigger_price: Decimal::from_ratio(trigger_return, offer_asset.amount),
+            });
+        }
+    }
+
+    if simul_res.return_amount < min_return {
+        return Err(ContractError::InsufficientReturn {
+            expected: min_return,
+            simulated: simul_res.return_amount,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn execute_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: u64,
+    amount: Option<Uint128>,
+    split: Option<Vec<SplitLeg>>,
+) -> Result<Response, ContractError> {
+    let pause = read_pause(deps.storage)?;
+    if pause.execute {
+        return Err(ContractError::Paused {
+            operation: "execution".to_string(),
+        });
+    }
+
+    let order: OrderInfo = read_order(deps.storage, order_id)?;
+    if !order.pending_fee_amount.is_zero() {
+        return Err(ContractError::FeeNotPaid {});
+    }
+    if order.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    if let Some(split) = &split {
+        assert_split(&order, split)?;
+        if let Some(leg) = split.iter().find(|leg| pause.is_dex_paused(&leg.pool.dex)) {
+            return Err(ContractError::Paused {
+                operation: format!("execution on {:?}", leg.pool.dex).to_lowercase(),
+            });
+        }
+        // pools deregistered since the submission are refused
+        for leg in split.iter() {
+            assert_pair_assets(
+                deps.storage,
+                &leg.pool.pair_addr,
+                &leg.pool.dex,
+                &order.offer_asset.info,
+                &order.ask_asset.info,
+            )?;
+        }
+    }
+    
+    // executor may fill only a part of the remaining offer, a split fills the sum of its legs
+    let split_amount = split
+        .as_ref()
+        .map(|split| split.iter().map(|leg| leg.amount).sum::<Uint128>());
+    if let (Some(amount), Some(split_amount)) = (amount, split_amount) {
+        if amount != split_amount {
+            return Err(ContractError::InvalidFillAmount {
+                reason: "amount should equal the sum of the split legs".to_string(),
+            });
+        }
+    }
+    let fill_amount = amount
+        .or(split_amount)
+        .or_else(|| order.tranche_amount())
+        .unwrap_or(order.offer_asset.amount);
+    if fill_amount.is_zero() || fill_amount > order.offer_asset.amount {
+        return Err(ContractError::InvalidFillAmount {
+                reason: format!(
+                "amount should be greater than 0 and less than or equal to {}",
+                order.offer_asset.amount
+            ),
+        });
+    }
+    if fill_amount < order.offer_asset.amount
+        && (order.recurring.is_some() || matches!(order.kind, OrderKind::Surfing { .. }))
+    {
+        return Err(ContractError::InvalidFillAmount {
+            reason: "recurring orders cannot be partially filled".to_string(),
+        });
+    }
+    if fill_amount < order.offer_asset.amount && order.linked_order_id.is_some() {
+        return Err(ContractError::InvalidFillAmount {
+            reason: "oco orders cannot be partially filled".to_string(),
+        });
+    }
+    if let OrderKind::Dca { interval, .. } = order.kind {
+        // dca orders fill one tranche per interval
+        if let Some(last_fill_time) = order.last_fill_time {
+            let next_fill_time = last_fill_time
+                .checked_add(interval)
+                .ok_or_else(|| OverflowError::new(OverflowOperation::Add, last_fill_time, interval))?;
+            if env.block.time.seconds() < next_fill_time {
+                return Err(ContractError::TooEarly {
+                    time: next_fill_time,
+                });
+            }
+        }
+        if Some(fill_amount) != order.tranche_amount() {
+            return Err(ContractError::InvalidFillAmount {
+                reason: format!(
+                    "amount should be the tranche amount {}",
+                    order.tranche_amount().unwrap_or_default()
+                ),
+            });
+        }
+    }
+
+    let fill_min_return = order.fill_min_return(fill_amount);
+    if fill_min_return.is_zero() {
+        return Err(ContractError::InvalidFillAmount {
+            reason: "amount is too small to fill".to_string(),
+        });
+    }
+
+    // swap legs as (pool, offer after tax, simulated return)
+    let legs: Vec<(Pool, Asset, Uint128)> = if let Some(split) = split {
+        split
+            .into_iter()
+            .map(|leg| {
+                let offer_asset = deduct_offer_tax(
+                    &deps.querier,
+                    Asset {
+                        amount: leg.amount,
+                        info: order.offer_asset.info.clone(),
+                    },
+                )?;
+                let simul_res =
+                    simulate_multipools(&deps.querier, &leg.pool.dex, &leg.pool.pair_addr, &offer_asset)?;
+                Ok((leg.pool, offer_asset, simul_res.return_amount))
+            })
+            .collect::<Result<_, ContractError>>()?
+    } else {
+        let offer_asset = deduct_offer_tax(
+            &deps.querier,
+            Asset {
+                amount: fill_amount,
+                info: order.offer_asset.info.clone(),
+            },
+        )?;
+        if let Some(route) = &order.route {
+            if pause.is_dex_paused(&order.dex) {
+                return Err(ContractError::Paused {
+                    operation: format!("execution on {:?}", order.dex).to_lowercase(),
+                });
+            }
+            // the router or a hop pair may have been deregistered since the submission
+            if ROUTERS.may_load(deps.storage, order.pair_addr.as_bytes())? != Some(order.dex) {
+                return Err(ContractError::RouterNotRegistered {
+                    router_addr: order.pair_addr.to_string(),
+                });
+            }
+            assert_route_pairs(deps.storage, &order.dex, route)?;
+            let return_amount =
+                order
+                    .dex
+                    .adapter()
+                    .simulate_route(&deps.querier, &order.pair_addr, &offer_asset, route)?;
+            vec![(order.pool(), offer_asset, return_amount)]
+        } else {
+            // paused dexes are left out of the candidates
+            let pools: Vec<Pool> = order
+                .pools()
+                .into_iter()
+                .filter(|pool| !pause.is_dex_paused(&pool.dex))
+                .collect();
+            if pools.is_empty() {
+                return Err(ContractError::Paused {
+                    operation: format!("execution on {:?}", order.dex).to_lowercase(),
+                });
+            }
+            // so are the pools deregistered since the submission
+            let pools: Vec<Pool> = pools
+                .into_iter()
+                .filter(|pool| {
+                    assert_pair_assets(
+                        deps.storage,
+                        &pool.pair_addr,
+                        &pool.dex,
+                        &order.offer_asset.info,
+                        &order.ask_asset.info,
+                    )
+                    .is_ok()
+                })
+                .collect();
+            if pools.is_empty() {
+                return Err(ContractError::PairNotRegistered {
+                    pair_addr: order.pair_addr.to_string(),
+                });
+            }
+            let (pool, simul_res) = best_pool(&deps.querier, &pools, &offer_asset)?;
+            vec![(pool, offer_asset, simul_res.return_amount)]
+        }
+    };
+
+    // the order is checked on the summed legs
+    let offer_asset = Asset {
+        amount: legs.iter().map(|(_, offer_asset, _)| offer_asset.amount).sum(),
+        info: order.offer_asset.info.clone(),
+    };
+    let simul_res = SimulationResponse {
+        return_amount: legs.iter().map(|(_, _, return_amount)| *return_amount).sum(),
+        spread_amount: Uint128::zero(),
+        commission_amount: Uint128::zero(),
+    };
+    assert_order_triggered(&order, &offer_asset, fill_min_return, &simul_res)?;
 
     // create swap message
     // fix bug swap on astroport bLUNA-LUNA return spread larger than 0.5% causing transaction to fail eventhough
     // actually less than 0.5% spread from belief_price
-    let belief_price: Option<Decimal> = Some(Decimal::from_ratio(offer_asset.amount, order.ask_asset.amount));
+    let belief_price: Option<Decimal> = Some(Decimal::from_ratio(offer_asset.amount, fill_min_return));
     // default to max_spread 0.5%
     // as of 2022/05/04 astroport apply default 0.5% max_spread while terraswap have none i.e. will not check spread if passing none
     let max_spread: Option<Decimal> = Some(Decimal::from_str("0.005")?);
-    match offer_asset.clone().info {
-        AssetInfo::Token { contract_addr } => {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr,
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Send {
-                    contract: order.pair_addr.to_string(),
-                    amount: offer_asset.amount,
-                    msg: to_binary(&PairCw20HookMsg::Swap {
-                        to: None,
-                        belief_price: belief_price,
-                        max_spread: max_spread,
-                    })?,
-                })?,
-            }));
+
+    let mut submessages: Vec<SubMsg> = vec![];
+    let mut attrs = vec![
+        attr("action", "execute_order"),
+        attr("order_id", order_id.to_string()),
+        attr("executor_addr", info.sender.to_string()),
+        attr("offer_amount", fill_amount.to_string()),
+    ];
+    for (i, (pool, leg_offer_asset, _)) in legs.iter().enumerate() {
+        let swap_msg = if let Some(route) = &order.route {
+            // the router checks the final return against minimum_receive instead of the spread
+            pool.dex.adapter().build_route_swap_msg(
+                &pool.pair_addr,
+                leg_offer_asset,
+                route,
+                Some(fill_min_return),
+            )?
+        } else {
+            pool.dex.adapter().build_swap_msg(
+                &pool.pair_addr,
+                leg_offer_asset,
+                belief_price,
+                max_spread,
+            )?
+        };
+
+        // legs run in order within the transaction, settlement follows the last one
+        submessages.push(if i + 1 == legs.len() {
+            SubMsg::reply_on_success(swap_msg, EXECUTE_ORDER_REPLY_ID)
+        } else {
+            SubMsg::new(swap_msg)
+        });
+        attrs.push(attr("pair_addr", pool.pair_addr.to_string()));
+        attrs.push(attr("dex", format!("{:?}", pool.dex).to_lowercase()));
+        attrs.push(attr("leg_offer_amount", leg_offer_asset.amount.to_string()));
+    }
+    attrs.push(attr("simulated_return_amount", simul_res.return_amount.to_string()));
+
+    // the swap is settled in settle_order on the actual return, measured as the change of
+    // the contract ask asset balance around the swap
+    let ask_balance_before = order.ask_asset.info.query_pool(
+        &deps.querier,
+        deps.api,
+        env.contract.address,
+    )?;
+    PENDING_EXECUTION.save(
+        deps.storage,
+        &PendingExecution {
+            order_id,
+            executor_addr: info.sender.clone(),
+            ask_balance_before,
+            offer_amount: fill_amount,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(submessages)
+        .add_attributes(attrs))
+}
+
+// split legs must use distinct pools of the order
+fn assert_split(order: &OrderInfo, split: &[SplitLeg]) -> Result<(), ContractError> {
+    if order.route.is_some() {
+        return Err(ContractError::InvalidSplit {
+            reason: "route orders cannot be split".to_string(),
+        });
+    }
+    if split.is_empty() {
+        return Err(ContractError::InvalidSplit {
+            reason: "split should have at least one leg".to_string(),
+        });
+    }
+
+    let pools = order.pools();
+    for (i, leg) in split.iter().enumerate() {
+        if leg.amount.is_zero() {
+            return Err(ContractError::InvalidSplit {
+                reason: "split leg amount should be greater than 0".to_string(),
+            });
         }
-        AssetInfo::NativeToken { denom } => {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: order.pair_addr.to_string(),
-                funds: vec![Coin {
-                    denom: denom.clone(),
-                    amount: offer_asset.amount,
-                }],
-                msg: if dex == POOL_PRISM {
-                    to_binary(&PrismPairExecuteMsg::Swap {
-                        offer_asset: 
-                            CwAsset {
-                                amount: offer_asset.amount,
-                                info: CwAssetInfo::Native(denom.clone())
-                            },
-                        belief_price: belief_price,
-                        max_spread: max_spread,
-                        to: None,
-                    })?
-                } else {
-                    to_binary(&PairExecuteMsg::Swap {
-                        offer_asset,
-                        belief_price: belief_price,
-                        max_spread: max_spread,
-                        to: None,
-                    })?
-                },
-            }));
+        if !pools.contains(&leg.pool) {
+            return Err(ContractError::InvalidSplit {
+                reason: format!(
+                    "pool {} is not a candidate pool of the order",
+                    leg.pool.pair_addr
+                ),
+            });
         }
+        if split[..i].iter().any(|other| other.pool == leg.pool) {
+            return Err(ContractError::InvalidSplit {
+                reason: format!(
+                    "pool {} is used by several split legs",
+                    leg.pool.pair_addr
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// deduct tax if native
+fn deduct_offer_tax(querier: &QuerierWrapper, offer_asset: Asset) -> Result<Asset, ContractError> {
+    if offer_asset.is_native_token() {
+        let amount = offer_asset.deduct_tax(querier)?.amount;
+
+        Ok(Asset {
+            amount,
+            ..offer_asset
+        })
+    } else {
+        Ok(offer_asset)
+    }
+}
+
+/// Reply handler of the execute_order swap, pays out the actual swap return
+pub fn settle_order(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pending: PendingExecution = PENDING_EXECUTION.load(deps.storage)?;
+    PENDING_EXECUTION.remove(deps.storage);
+
+    let order: OrderInfo = read_order(deps.storage, pending.order_id)?;
+
+    let ask_balance_after = order.ask_asset.info.query_pool(
+        &deps.querier,
+        deps.api,
+        env.contract.address,
+    )?;
+    let return_amount = ask_balance_after.checked_sub(pending.ask_balance_before)?;
+
+    // ask, min return and fee are pro-rated to the filled part of the offer
+    let is_partial_fill = pending.offer_amount < order.offer_asset.amount;
+    let fill_ask_amount = order
+        .ask_asset
+        .amount
+        .multiply_ratio(pending.offer_amount, order.offer_asset.amount);
+    let fill_min_return = order.fill_min_return(pending.offer_amount);
+    if return_amount < fill_min_return {
+        return Err(ContractError::InsufficientReturn {
+            expected: fill_min_return,
+            simulated: return_amount,
+        });
+    }
+
+    // stop-loss, dca and surfing bidders receive the whole return, others receive the ask amount
+    let bidder_amount = match order.kind {
+        OrderKind::StopLoss { .. }
+        | OrderKind::TrailingStop { .. }
+        | OrderKind::Dca { .. }
+        | OrderKind::Surfing { .. } => return_amount,
+        OrderKind::TakeProfit {} => fill_ask_amount,
     };
 
+    let mut messages: Vec<CosmosMsg> = vec![];
+
     // keep asset for new order if the order is recurring
     // let mut remaining_loop = 0;
     let mut is_last_order = true;
-    let mut fee_amount = order.fee_amount;
+    let mut fee_amount = match order.kind {
+        // fee for current tranche, fee left = current order.fee_amount - fee_amount
+        OrderKind::Dca { tranches, .. } => order.fee_amount.multiply_ratio(1u128, tranches),
+        _ => order
+            .fee_amount
+            .multiply_ratio(pending.offer_amount, order.offer_asset.amount),
+    };
     if let Some(recurring) = order.recurring.clone() {
         // remaining_loop = recurring.remaining_loop
         if recurring.remaining_loop > 0 {
@@ -277,42 +1621,116 @@ pub fn execute_order(deps: DepsMut, _env: Env, _info: MessageInfo, order_id: u64
             is_last_order = false;
         }
     }
+    if let OrderKind::Surfing { remaining_loop, .. } = order.kind {
+        if remaining_loop > 0 {
+            fee_amount = order.fee_amount.multiply_ratio(1u128, remaining_loop + 1);
 
-    // else send asset to bidder
-    if is_last_order {
-        messages.push(
-            order
-                .ask_asset
-                .clone()
-                .into_msg(&deps.querier, order.bidder_addr.clone())?,
-        );
+            is_last_order = false;
+        }
     }
+    let fill_price = Decimal::from_ratio(return_amount, pending.offer_amount);
 
-    // executor might earn config.executor_fee_percent amount of excess and fee, but 
-    // for simplicity it is disabled now. Will explore this option later.
+    // hodler buy backs gain the base bought back above the base sold, the last one included
+    if let Some(hodler) = &order.hodler {
+        if order.ask_asset.info == hodler.base.info {
+            let base_gain = order.ask_asset.amount.checked_sub(hodler.base.amount)?;
+            let origin_order_id = hodler.origin_order_id.unwrap_or(order.order_id);
+            HODLER_GAINS.update(deps.storage, &origin_order_id.to_be_bytes(), |gain| -> StdResult<_> {
+                let mut gain = gain.ok_or_else(|| StdError::not_found("HodlerGain"))?;
+                gain.base_gain += base_gain;
+                Ok(gain)
+            })?;
+        }
+    }
 
-    // send excess amount to reserve
-    let excess_amount: Uint128 = simul_res.return_amount - order.ask_asset.amount;
-    if excess_amount > Uint128::zero() {
-        let excess_asset = Asset {
-            amount: excess_amount,
+    // else send asset to bidder
+    if is_last_order {
+        let bidder_asset = Asset {
+            amount: bidder_amount,
             info: order.ask_asset.info.clone(),
         };
-        messages.push(excess_asset.into_msg(&deps.querier, deps.api.addr_validate(&config.reserve_addr)?)?);
+        messages.push(bidder_asset.into_msg(&deps.querier, order.bidder_addr.clone())?);
     }
 
-    // send fee to reserve, take a portion of fee equivalent to number of loop
-    if !fee_amount.is_zero() {
-        let fee_asset = Asset {
-            amount: fee_amount,
-            info: config.fee_token.clone()
-        };
-        messages.push(fee_asset.clone().into_msg(&deps.querier, deps.api.addr_validate(&config.reserve_addr)?)?);
+    let reserve_addr = deps.api.addr_validate(&config.reserve_addr)?;
+
+    // executor earns config.executor_fee_percent of the excess and the fee, the rest goes to reserve
+    let excess_amount: Uint128 = return_amount - bidder_amount;
+    let (executor_excess_amount, reserve_excess_amount) = split_executor_share(&config, excess_amount);
+    let (executor_fee_amount, reserve_fee_amount) = split_executor_share(&config, fee_amount);
+
+    for (amount, info, recipient) in [
+        (executor_excess_amount, &order.ask_asset.info, &pending.executor_addr),
+        (reserve_excess_amount, &order.ask_asset.info, &reserve_addr),
+        (executor_fee_amount, &config.fee_token, &pending.executor_addr),
+        (reserve_fee_amount, &config.fee_token, &reserve_addr),
+    ] {
+        if !amount.is_zero() {
+            let asset = Asset {
+                amount,
+                info: info.clone(),
+            };
+            messages.push(asset.into_msg(&deps.querier, recipient.clone())?);
+        }
     }
 
-    remove_order(deps.storage, &order);
+    if is_partial_fill {
+        // keep the rest of the order open
+        let mut remaining_order = order.clone();
+        remaining_order.offer_asset.amount -= pending.offer_amount;
+        remaining_order.ask_asset.amount -= fill_ask_amount;
+        remaining_order.fee_amount -= fee_amount;
+        match remaining_order.kind {
+            OrderKind::StopLoss { ref mut min_return, .. }
+            | OrderKind::TrailingStop { ref mut min_return, .. } => *min_return -= fill_min_return,
+            OrderKind::Dca { ref mut tranches, .. } => *tranches -= 1,
+            OrderKind::TakeProfit {} | OrderKind::Surfing { .. } => {}
+        }
+        remaining_order.filled_offer += pending.offer_amount;
+        remaining_order.filled_ask += bidder_amount;
+        remaining_order.last_fill_time = Some(env.block.time.seconds());
+        remaining_order.last_fill_price = Some(fill_price);
+        ORDERS.save(deps.storage, &order.order_id.to_be_bytes(), &remaining_order)?;
+    } else {
+        remove_order(deps.storage, &order);
+    }
+    // the oco sibling shared the swapped escrow
+    let linked_order_id = remove_linked_order(deps.storage, &order)?;
+
+    if let OrderKind::Surfing { band_percent, remaining_loop } = order.kind {
+        if !is_last_order {
+            // offer the whole return back, asking band_percent more than the filled offer
+            let ask_amount = pending
+                .offer_amount
+                .checked_mul(Uint128::from(FEE_PERCENT_PRECISION).checked_add(band_percent)?)?
+                / Uint128::from(FEE_PERCENT_PRECISION);
+            let mut new_order = OrderInfo {
+                order_id: 0u64, // provisional
+                offer_asset: Asset {
+                    amount: return_amount,
+                    info: order.ask_asset.info.clone(),
+                },
+                ask_asset: Asset {
+                    amount: ask_amount,
+                    info: order.offer_asset.info.clone(),
+                },
+                fee_amount: order.fee_amount - fee_amount,
+                kind: OrderKind::Surfing {
+                    band_percent,
+                    remaining_loop: remaining_loop - 1,
+                },
+                filled_offer: Uint128::zero(),
+                filled_ask: Uint128::zero(),
+                route: order.route.clone().map(reverse_route),
+                last_fill_time: None,
+                last_fill_price: Some(fill_price),
+                ..order.clone()
+            };
+            store_new_order(deps.storage, &mut new_order)?;
+        }
+    }
 
-    if !is_last_order {
+    if let (false, Some(recurring)) = (is_last_order, order.recurring.clone()) {
         // reverse offer_asset and ask_asset
         // belief_price = offer_asset.amount / ask_asset.amount
         // case 1: start with 100 LUNA, sell at 90$ and buy back at 85$ and repeat
@@ -327,8 +1745,24 @@ pub fn execute_order(deps: DepsMut, _env: Env, _info: MessageInfo, order_id: u64
         // execute order 2nd: 100 LUNA -> 100*(1/0.011111) = 9000 UST (*1/swapback_belief_price)
         // on next swap 9000 UST -> 9000*1/85.0 = 105.88 LUNA (*1/belief_price)
 
-        let new_offer_asset = order.ask_asset.clone();
-        let recurring = order.recurring.unwrap();
+        let mut new_offer_asset = order.ask_asset.clone();
+
+        let mut hodler = order.hodler;
+        if let Some(hodler) = &mut hodler {
+            hodler.origin_order_id.get_or_insert(order.order_id);
+            if new_offer_asset.info == hodler.base.info {
+                // hodler buy back: resell only the base amount sold and pay out the surplus
+                let base_surplus = new_offer_asset.amount.checked_sub(hodler.base.amount)?;
+                if !base_surplus.is_zero() {
+                    let surplus_asset = Asset {
+                        amount: base_surplus,
+                        info: hodler.base.info.clone(),
+                    };
+                    messages.push(surplus_asset.into_msg(&deps.querier, order.bidder_addr.clone())?);
+                }
+                new_offer_asset.amount = hodler.base.amount;
+            }
+        }
 
         let amount = if (recurring.total_loop - recurring.remaining_loop) % 2 == 0 {
             // after 1st, 3rd, 5th ... execution
@@ -342,26 +1776,58 @@ pub fn execute_order(deps: DepsMut, _env: Env, _info: MessageInfo, order_id: u64
             amount,    
             ..order.offer_asset.clone()
         };
+        if let Some(hodler) = &hodler {
+            if new_ask_asset.info == hodler.base.info && new_ask_asset.amount <= hodler.base.amount {
+                return Err(ContractError::InvalidOrder {
+                    reason: "hodler buy back should return more than the base sold".to_string(),
+                });
+            }
+        }
 
         let mut new_order = OrderInfo {
             order_id: 0u64, // provisional
             bidder_addr: order.bidder_addr,
             pair_addr: order.pair_addr,
+            dex: order.dex,
             offer_asset: new_offer_asset,
             ask_asset: new_ask_asset,
             fee_amount: if is_last_order { order.fee_amount } else { order.fee_amount - fee_amount },
+            pending_fee_amount: Uint128::zero(),
             recurring: Some(RecurringOrderOpt {
                     remaining_loop: recurring.remaining_loop - 1,
                     ..recurring
-                })
+                }),
+            expires_at: order.expires_at,
+            kind: order.kind,
+            filled_offer: Uint128::zero(),
+            filled_ask: Uint128::zero(),
+            route: order.route.map(reverse_route),
+            candidate_pools: order.candidate_pools,
+            last_fill_time: None,
+            last_fill_price: None,
+            hodler,
+            grid_id: None,
+            linked_order_id: None,
+            high_water_price: None,
+            last_poke_time: None,
         };
         store_new_order(deps.storage, &mut new_order)?;
     };
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_order"),
+        attr("action", "settle_order"),
         attr("order_id", order.order_id.to_string()),
+        attr("executor_addr", pending.executor_addr.to_string()),
+        attr("offer_amount", pending.offer_amount.to_string()),
+        attr("return_amount", return_amount.to_string()),
+        attr("fill_price", fill_price.to_string()),
+        attr("linked_order_id", linked_order_id.map(|id| id.to_string()).unwrap_or_default()),
+        attr("partial_fill", is_partial_fill.to_string()),
         attr("fee_amount", fee_amount.to_string()),
         attr("excess_amount", excess_amount.to_string()),
+        attr("executor_fee_amount", executor_fee_amount.to_string()),
+        attr("executor_excess_amount", executor_excess_amount.to_string()),
+        attr("reserve_fee_amount", reserve_fee_amount.to_string()),
+        attr("reserve_excess_amount", reserve_excess_amount.to_string()),
     ]))
 }
@@ -1,20 +1,33 @@
 use cosmwasm_std::{Deps, StdResult};
+use terraswap::asset::Asset;
 
 use crate::{
-    msg::{ConfigResponse, LastOrderIdResponse, OrderBy, OrderResponse, OrdersResponse},
-    state::{read_orders, read_orders_by_user, Config, OrderInfo, CONFIG, LAST_ORDER_ID, ORDERS},
+    error::ContractError,
+    msg::{
+        ConfigResponse, HodlerGainResponse, LastOrderIdResponse, OrderBy, OrderResponse, OrdersResponse,
+        PairResponse, PairsResponse, PauseStatusResponse, RequiredFeeResponse, RouterResponse,
+        RoutersResponse,
+    },
+    order::compute_required_fee,
+    state::{
+        read_order, read_orders, read_orders_by_grid, read_orders_by_user, read_pairs, read_pause, read_routers,
+        Config,
+        OrderInfo, PairInfo, CONFIG, HODLER_GAINS, LAST_ORDER_ID, PAIRS, PENDING_OWNER,
+    },
 };
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
 
-    config.as_res()
+    let mut res = config.as_res()?;
+    res.pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(res)
 }
 
-pub fn query_order(deps: Deps, order_id: u64) -> StdResult<OrderResponse> {
-    let order: OrderInfo = ORDERS.load(deps.storage, &order_id.to_be_bytes())?;
+pub fn query_order(deps: Deps, order_id: u64) -> Result<OrderResponse, ContractError> {
+    let order: OrderInfo = read_order(deps.storage, order_id)?;
 
-    order.as_res()
+    Ok(order.as_res()?)
 }
 
 pub fn query_orders(
@@ -23,7 +36,7 @@ pub fn query_orders(
     start_after: Option<u64>,
     limit: Option<u32>,
     order_by: Option<OrderBy>,
-) -> StdResult<OrdersResponse> {
+) -> Result<OrdersResponse, ContractError> {
     let orders: Vec<OrderInfo> = if let Some(bidder_addr) = bidder_addr {
         read_orders_by_user(
             deps.storage,
@@ -46,8 +59,100 @@ pub fn query_orders(
     Ok(resp)
 }
 
-pub fn query_last_order_id(deps: Deps) -> StdResult<LastOrderIdResponse> {
+pub fn query_grid_orders(deps: Deps, grid_id: u64) -> Result<OrdersResponse, ContractError> {
+    let orders = read_orders_by_grid(deps.storage, grid_id)?;
+
+    Ok(OrdersResponse {
+        orders: orders
+            .iter()
+            .map(|order| order.as_res())
+            .collect::<StdResult<Vec<OrderResponse>>>()?,
+    })
+}
+
+pub fn query_last_order_id(deps: Deps) -> Result<LastOrderIdResponse, ContractError> {
     let last_order_id = LAST_ORDER_ID.load(deps.storage)?;
 
     Ok(LastOrderIdResponse { last_order_id })
 }
+
+pub fn query_required_fee(deps: Deps, offer_asset: Asset) -> Result<RequiredFeeResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let fee_amount = compute_required_fee(deps.storage, &deps.querier, &config, &offer_asset)?;
+
+    Ok(RequiredFeeResponse {
+        fee_token: config.fee_token,
+        fee_amount,
+    })
+}
+
+pub fn query_hodler_gain(deps: Deps, order_id: u64) -> Result<HodlerGainResponse, ContractError> {
+    let gain = HODLER_GAINS
+        .may_load(deps.storage, &order_id.to_be_bytes())?
+        .ok_or_else(|| ContractError::InvalidOrder {
+            reason: "order is not a submitted hodler order".to_string(),
+        })?;
+
+    Ok(HodlerGainResponse {
+        order_id,
+        base_asset: gain.base_asset,
+        base_gain: gain.base_gain,
+    })
+}
+
+pub fn query_pause_status(deps: Deps) -> Result<PauseStatusResponse, ContractError> {
+    let pause = read_pause(deps.storage)?;
+
+    Ok(PauseStatusResponse {
+        submit: pause.submit,
+        execute: pause.execute,
+        paused_dexes: pause.paused_dexes,
+    })
+}
+
+pub fn query_pair(deps: Deps, pair_addr: String) -> Result<PairResponse, ContractError> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair: PairInfo = PAIRS
+        .may_load(deps.storage, pair_addr.as_bytes())?
+        .ok_or(ContractError::PairNotRegistered {
+            pair_addr: pair_addr.to_string(),
+        })?;
+
+    Ok(pair.as_res())
+}
+
+pub fn query_pairs(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<PairsResponse, ContractError> {
+    let start_after = start_after
+        .map(|pair_addr| deps.api.addr_validate(&pair_addr))
+        .transpose()?;
+    let pairs = read_pairs(deps.storage, start_after.as_ref(), limit)?;
+
+    Ok(PairsResponse {
+        pairs: pairs.iter().map(|pair| pair.as_res()).collect(),
+    })
+}
+
+pub fn query_routers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<RoutersResponse, ContractError> {
+    let start_after = start_after
+        .map(|router_addr| deps.api.addr_validate(&router_addr))
+        .transpose()?;
+    let routers = read_routers(deps.storage, start_after.as_ref(), limit)?;
+
+    Ok(RoutersResponse {
+        routers: routers
+            .into_iter()
+            .map(|(router_addr, dex)| RouterResponse {
+                router_addr: router_addr.to_string(),
+                dex,
+            })
+            .collect(),
+    })
+}
@@ -2,48 +2,208 @@ use cw_storage_plus::{Bound, Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128, Decimal};
+use cosmwasm_std::{Addr, BlockInfo, Order, StdResult, Storage, Uint128, Decimal};
+use cw20::Expiration;
 use terraswap::asset::{Asset, AssetInfo};
+use terraswap::router::SwapOperation;
 
-use crate::msg::{ConfigResponse, OrderBy, OrderResponse};
+use crate::error::ContractError;
+use crate::msg::{ConfigResponse, Dex, OrderBy, OrderResponse, PairResponse};
 
 pub const CONFIG: Item<Config> = Item::new("config");
+// config stored by the versions without owner, read once by migrate
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+// owner proposed by the current owner, see ExecuteMsg::ProposeNewOwner
+pub const PENDING_OWNER: Item<String> = Item::new("pending_owner");
+// see ExecuteMsg::SetPause, nothing is paused until it is first set
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
 pub const LAST_ORDER_ID: Item<u64> = Item::new("last_order_id");
 pub const ORDERS: Map<&[u8], OrderInfo> = Map::new("orders");
 pub const ORDERS_BY_USER: Map<(&[u8], &[u8]), bool> = Map::new("orders_by_user");
+pub const PENDING_EXECUTION: Item<PendingExecution> = Item::new("pending_execution");
+pub const LAST_GRID_ID: Item<u64> = Item::new("last_grid_id");
+// pairs orders can be submitted on, keyed by pair address
+pub const PAIRS: Map<&[u8], PairInfo> = Map::new("pairs");
+// registered pair of each dex and asset pair, see pair_assets_key
+pub const PAIRS_BY_ASSETS: Map<(&[u8], &[u8]), Addr> = Map::new("pairs_by_assets");
+// routers route orders can be submitted on, keyed by router address
+pub const ROUTERS: Map<&[u8], Dex> = Map::new("routers");
+pub const ORDERS_BY_GRID: Map<(&[u8], &[u8]), bool> = Map::new("orders_by_grid");
+// base asset gained by a hodler order and its loops, keyed by the id of the submitted order
+pub const HODLER_GAINS: Map<&[u8], HodlerGain> = Map::new("hodler_gains");
 
-pub const POOL_PRISM: &str = "prism";
+pub const EXECUTE_ORDER_REPLY_ID: u64 = 1;
+
+pub const MAX_GRID_LEVELS: u64 = 20;
+// minimum seconds between two pokes of a trailing stop order
+pub const POKE_INTERVAL: u64 = 60;
+
+// denominator of min_fee_percent and executor_fee_percent
+pub const FEE_PERCENT_PRECISION: u128 = 1000000;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    // only the owner can change the config
+    pub owner: String,
     pub fee_token: AssetInfo,
     pub min_fee_amount: Uint128,
+    // guardian can pause the contract along with the owner
+    pub guardian: Option<String>,
     // min_fee_percent 1000 ~ 1000/1000000 = 0.1%
     pub min_fee_percent: Uint128,
     pub executor_fee_percent: Uint128,
     pub reserve_addr: String,
+    pub terraswap_factory: String,
 }
 
 impl Config {
     pub fn as_res(&self) -> StdResult<ConfigResponse> {
         let res = ConfigResponse {
+            owner: self.owner.clone(),
+            pending_owner: None,
             fee_token: self.fee_token.clone(),
             min_fee_amount: self.min_fee_amount,
+            guardian: self.guardian.clone(),
             min_fee_percent: self.min_fee_percent,
             executor_fee_percent: self.executor_fee_percent,
             reserve_addr: self.reserve_addr.clone(),
+            terraswap_factory: self.terraswap_factory.clone(),
         };
         Ok(res)
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseInfo {
+    // blocks new orders
+    pub submit: bool,
+    // blocks all executions
+    pub execute: bool,
+    // blocks executions swapping on these dexes
+    pub paused_dexes: Vec<Dex>,
+}
+
+impl PauseInfo {
+    pub fn is_dex_paused(&self, dex: &Dex) -> bool {
+        self.paused_dexes.contains(dex)
+    }
+}
+
+// pair registered by the owner, asset_infos are checked against the pair on registration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairInfo {
+    pub pair_addr: Addr,
+    pub dex: Dex,
+    pub asset_infos: [AssetInfo; 2],
+}
+
+impl PairInfo {
+    pub fn as_res(&self) -> PairResponse {
+        PairResponse {
+            pair_addr: self.pair_addr.to_string(),
+            dex: self.dex,
+            asset_infos: self.asset_infos.clone(),
+        }
+    }
+}
+
+pub fn read_pause(storage: &dyn Storage) -> StdResult<PauseInfo> {
+    Ok(PAUSE.may_load(storage)?.unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub fee_token: AssetInfo,
+    pub min_fee_amount: Uint128,
+    pub min_fee_percent: Uint128,
+    pub executor_fee_percent: Uint128,
+    pub reserve_addr: String,
+    // missing in the first deployed version
+    pub terraswap_factory: Option<String>,
+}
+
+// execute_order context kept until the swap reply is handled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingExecution {
+    pub order_id: u64,
+    pub executor_addr: Addr,
+    pub ask_balance_before: Uint128,
+    // part of the order offer swapped by this execution
+    pub offer_amount: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RecurringOrderOpt {
     pub belief_price: Decimal,
     pub swapback_belief_price: Decimal,
     // total_loop = 1 meaning there are total two order, the current order and one loop order
     pub total_loop: u64,
-    pub remaining_loop: u64
+    pub remaining_loop: u64,
+    // buy back more base asset (the first offer asset) than sold and resell only the amount sold,
+    // the surplus base asset is paid to the bidder after each buy back
+    #[serde(default)]
+    pub hodler: bool,
+}
+
+// base asset bookkeeping of hodler orders, carried forward to the loop orders
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HodlerInfo {
+    // base asset sold by every sell leg
+    pub base: Asset,
+    // id of the submitted order the loop orders come from, none on the submitted order
+    pub origin_order_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HodlerGain {
+    pub base_asset: AssetInfo,
+    // surplus base asset paid out so far, kept after the last loop
+    pub base_gain: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Pool {
+    pub pair_addr: Addr,
+    pub dex: Dex,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    // fill when the simulated return reaches ask_asset.amount, the excess goes to executor and reserve
+    TakeProfit {},
+    // fill when the price (return amount per offer amount) drops to or below trigger_price
+    // and the return is at least min_return, the bidder receives the whole return
+    StopLoss {
+        trigger_price: Decimal,
+        min_return: Uint128,
+    },
+    // swap offer_asset in equal tranches, one every interval seconds at the earliest,
+    // each tranche returning at least per_tranche_min_return, the bidder receives the whole return
+    // tranches counts the tranches left to run
+    Dca {
+        interval: u64,
+        tranches: u64,
+        per_tranche_min_return: Uint128,
+    },
+    // fill when the price (return amount per offer amount) drops trail_percent (per 1,000,000)
+    // below high_water_price and the return is at least min_return, the bidder receives the whole return
+    TrailingStop {
+        trail_percent: Uint128,
+        min_return: Uint128,
+    },
+    // fill like take_profit, then offer the whole return in the opposite direction asking
+    // band_percent (per 1,000,000) more than the last fill, remaining_loop times
+    Surfing {
+        band_percent: Uint128,
+        remaining_loop: u64,
+    },
+}
+
+impl Default for OrderKind {
+    fn default() -> Self {
+        OrderKind::TakeProfit {}
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -51,27 +211,124 @@ pub struct OrderInfo {
     pub order_id: u64,
     pub bidder_addr: Addr,
     pub pair_addr: Addr,
+    // orders stored before dexes were recorded used the terraswap pair interface
+    #[serde(default)]
+    pub dex: Dex,
     pub offer_asset: Asset,
     pub ask_asset: Asset,
     pub fee_amount: Uint128,
-    pub recurring: Option<RecurringOrderOpt>
+    // fee declared on a cw20 hook submission, not yet paid
+    #[serde(default)]
+    pub pending_fee_amount: Uint128,
+    pub recurring: Option<RecurringOrderOpt>,
+    pub expires_at: Option<Expiration>,
+    // orders stored before kinds existed are take-profit orders
+    #[serde(default)]
+    pub kind: OrderKind,
+    // offer swapped and ask paid out by partial fills so far
+    #[serde(default)]
+    pub filled_offer: Uint128,
+    #[serde(default)]
+    pub filled_ask: Uint128,
+    // multi-hop route swapped through the pair_addr router instead of a single pair
+    pub route: Option<Vec<SwapOperation>>,
+    // pools of the same asset pair competing with pair_addr on execution
+    pub candidate_pools: Option<Vec<Pool>>,
+    // block time in seconds of the last partial fill
+    pub last_fill_time: Option<u64>,
+    // return amount per offer amount of the last fill, carried to surfing loop orders
+    pub last_fill_price: Option<Decimal>,
+    pub hodler: Option<HodlerInfo>,
+    // grid the order was submitted with, see submit_grid
+    pub grid_id: Option<u64>,
+    // oco sibling sharing the escrowed offer and fee, see submit_oco
+    pub linked_order_id: Option<u64>,
+    // highest price seen by a trailing stop order and block time in seconds of the last poke
+    pub high_water_price: Option<Decimal>,
+    pub last_poke_time: Option<u64>,
 }
 
 impl OrderInfo {
+    pub fn pool(&self) -> Pool {
+        Pool {
+            pair_addr: self.pair_addr.clone(),
+            dex: self.dex,
+        }
+    }
+
+    // pair_addr followed by the candidate pools
+    pub fn pools(&self) -> Vec<Pool> {
+        let mut pools = vec![self.pool()];
+        if let Some(candidate_pools) = &self.candidate_pools {
+            pools.extend(candidate_pools.iter().cloned());
+        }
+        pools
+    }
+
+    // minimum swap return accepted to fill fill_amount of the offer
+    pub fn fill_min_return(&self, fill_amount: Uint128) -> Uint128 {
+        match self.kind {
+            OrderKind::TakeProfit {} | OrderKind::Surfing { .. } => self
+                .ask_asset
+                .amount
+                .multiply_ratio(fill_amount, self.offer_asset.amount),
+            OrderKind::StopLoss { min_return, .. } | OrderKind::TrailingStop { min_return, .. } => {
+                min_return.multiply_ratio(fill_amount, self.offer_asset.amount)
+            }
+            OrderKind::Dca { per_tranche_min_return, .. } => per_tranche_min_return,
+        }
+    }
+
+    // offer amount of the next dca tranche, the last tranche swaps the rest of the offer
+    pub fn tranche_amount(&self) -> Option<Uint128> {
+        match self.kind {
+            OrderKind::Dca { tranches, .. } => {
+                Some(self.offer_asset.amount.multiply_ratio(1u128, tranches))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at.is_expired(block))
+    }
+
     pub fn as_res(&self) -> StdResult<OrderResponse> {
         let res = OrderResponse {
             order_id: self.order_id,
             bidder_addr: self.bidder_addr.to_string(),
             pair_addr: self.pair_addr.to_string(),
+            dex: self.dex,
             offer_asset: self.offer_asset.clone(),
             ask_asset: self.ask_asset.clone(),
             fee_amount: self.fee_amount,
+            pending_fee_amount: self.pending_fee_amount,
             recurring: self.recurring.clone(),
+            expires_at: self.expires_at,
+            kind: self.kind.clone(),
+            filled_offer: self.filled_offer,
+            filled_ask: self.filled_ask,
+            route: self.route.clone(),
+            candidate_pools: self.candidate_pools.clone(),
+            last_fill_time: self.last_fill_time,
+            last_fill_price: self.last_fill_price,
+            hodler: self.hodler.clone(),
+            grid_id: self.grid_id,
+            linked_order_id: self.linked_order_id,
+            high_water_price: self.high_water_price,
+            last_poke_time: self.last_poke_time,
         };
         Ok(res)
     }
 }
 
+pub fn read_order(storage: &dyn Storage, order_id: u64) -> Result<OrderInfo, ContractError> {
+    ORDERS
+        .may_load(storage, &order_id.to_be_bytes())?
+        .ok_or(ContractError::OrderNotFound { id: order_id })
+}
+
 pub fn store_new_order(storage: &mut dyn Storage, order: &mut OrderInfo) -> StdResult<()> {
     let new_id: u64 = LAST_ORDER_ID.load(storage)? + 1u64;
     order.order_id = new_id;
@@ -82,6 +339,9 @@ pub fn store_new_order(storage: &mut dyn Storage, order: &mut OrderInfo) -> StdR
         (order.bidder_addr.as_bytes(), &new_id.to_be_bytes()),
         &true,
     )?;
+    if let Some(grid_id) = order.grid_id {
+        ORDERS_BY_GRID.save(storage, (&grid_id.to_be_bytes(), &new_id.to_be_bytes()), &true)?;
+    }
     LAST_ORDER_ID.save(storage, &new_id)?;
 
     Ok(())
@@ -93,6 +353,20 @@ pub fn remove_order(storage: &mut dyn Storage, order: &OrderInfo) {
         storage,
         (order.bidder_addr.as_bytes(), &order.order_id.to_be_bytes()),
     );
+    if let Some(grid_id) = order.grid_id {
+        ORDERS_BY_GRID.remove(storage, (&grid_id.to_be_bytes(), &order.order_id.to_be_bytes()));
+    }
+}
+
+// remove the sibling sharing the escrow of an oco order, returns its id
+pub fn remove_linked_order(storage: &mut dyn Storage, order: &OrderInfo) -> StdResult<Option<u64>> {
+    if let Some(linked_order_id) = order.linked_order_id {
+        if let Some(linked_order) = ORDERS.may_load(storage, &linked_order_id.to_be_bytes())? {
+            remove_order(storage, &linked_order);
+        }
+    }
+
+    Ok(order.linked_order_id)
 }
 
 // settings for pagination
@@ -131,6 +405,119 @@ pub fn read_orders_by_user(
         .collect()
 }
 
+// index key of a dex and an unordered asset pair
+fn pair_assets_key(dex: &Dex, asset_infos: &[AssetInfo; 2]) -> (Vec<u8>, Vec<u8>) {
+    let mut asset_keys = [asset_infos[0].to_string(), asset_infos[1].to_string()];
+    asset_keys.sort();
+
+    (format!("{:?}", dex).into_bytes(), asset_keys.join(",").into_bytes())
+}
+
+// a later registration of the same dex and assets takes over the index
+pub fn store_pair(storage: &mut dyn Storage, pair: &PairInfo) -> StdResult<()> {
+    if let Some(previous_pair) = PAIRS.may_load(storage, pair.pair_addr.as_bytes())? {
+        remove_pair(storage, &previous_pair)?;
+    }
+
+    PAIRS.save(storage, pair.pair_addr.as_bytes(), pair)?;
+    let (dex_key, assets_key) = pair_assets_key(&pair.dex, &pair.asset_infos);
+    PAIRS_BY_ASSETS.save(storage, (&dex_key, &assets_key), &pair.pair_addr)
+}
+
+pub fn remove_pair(storage: &mut dyn Storage, pair: &PairInfo) -> StdResult<()> {
+    PAIRS.remove(storage, pair.pair_addr.as_bytes());
+
+    // the index may point to another pair registered later for the same assets
+    let (dex_key, assets_key) = pair_assets_key(&pair.dex, &pair.asset_infos);
+    if PAIRS_BY_ASSETS.may_load(storage, (&dex_key, &assets_key))?.as_ref() == Some(&pair.pair_addr) {
+        PAIRS_BY_ASSETS.remove(storage, (&dex_key, &assets_key));
+    }
+
+    Ok(())
+}
+
+pub fn read_pair_by_assets(
+    storage: &dyn Storage,
+    dex: &Dex,
+    asset_infos: &[AssetInfo; 2],
+) -> StdResult<Option<Addr>> {
+    let (dex_key, assets_key) = pair_assets_key(dex, asset_infos);
+
+    PAIRS_BY_ASSETS.may_load(storage, (&dex_key, &assets_key))
+}
+
+pub fn read_pairs(
+    storage: &dyn Storage,
+    start_after: Option<&Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PairInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|pair_addr| Bound::exclusive(pair_addr.as_bytes()));
+
+    PAIRS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn read_routers(
+    storage: &dyn Storage,
+    start_after: Option<&Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, Dex)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|router_addr| Bound::exclusive(router_addr.as_bytes()));
+
+    ROUTERS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, dex) = item?;
+            Ok((Addr::unchecked(String::from_utf8_lossy(&k)), dex))
+        })
+        .collect()
+}
+
+// open orders of a grid, a grid has at most MAX_GRID_LEVELS orders
+pub fn read_orders_by_grid(storage: &dyn Storage, grid_id: u64) -> StdResult<Vec<OrderInfo>> {
+    ORDERS_BY_GRID
+        .prefix(&grid_id.to_be_bytes())
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, _) = item?;
+            ORDERS.load(storage, &k)
+        })
+        .collect()
+}
+
+// first open orders of a user in ascending order, only the ones on pair_addr if given
+pub fn read_orders_by_user_pair(
+    storage: &dyn Storage,
+    user: &Addr,
+    pair_addr: Option<&Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OrderInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    ORDERS_BY_USER
+        .prefix(user.as_bytes())
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, _) = item?;
+            ORDERS.load(storage, &k)
+        })
+        .filter(|order| match (order, pair_addr) {
+            (Ok(order), Some(pair_addr)) => order.pair_addr == *pair_addr,
+            _ => true,
+        })
+        .take(limit)
+        .collect()
+}
+
 pub fn read_orders(
     storage: &dyn Storage,
     start_after: Option<u64>,
@@ -0,0 +1,163 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{to_binary, BankMsg, Coin, CosmosMsg, Decimal, Response, SubMsg, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use terraswap::asset::Asset;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::{Cw20HookMsg, Dex, ExecuteMsg};
+use crate::state::read_order;
+use crate::testing::{
+    attr_value, setup, submit_order, token, uluna, MockDeps, ASK_TOKEN, BIDDER, FEE_TOKEN, OWNER, PAIR,
+};
+
+const OTHER_PAIR: &str = "pair0001";
+
+fn cancel_orders(
+    deps: &mut MockDeps,
+    order_ids: Option<Vec<u64>>,
+    pair_addr: Option<&str>,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[]),
+        ExecuteMsg::CancelOrders {
+            order_ids,
+            pair_addr: pair_addr.map(|pair_addr| pair_addr.to_string()),
+            limit: None,
+        },
+    )
+}
+
+// BIDDER sends offer_amount ASK_TOKEN for as much uluna, leaving the uluna fee pending
+fn submit_cw20_order(deps: &mut MockDeps, offer_amount: u128) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ASK_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: BIDDER.to_string(),
+            amount: Uint128::from(offer_amount),
+            msg: to_binary(&Cw20HookMsg::SubmitOrder {
+                pair_addr: PAIR.to_string(),
+                dex: Dex::Terraswap,
+                ask_asset: Asset {
+                    info: uluna(),
+                    amount: Uint128::from(offer_amount),
+                },
+                fee_amount: Uint128::from(100u128),
+                recurring: None,
+                expires_at: None,
+                kind: None,
+                route: None,
+                candidate_pools: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+}
+
+fn bank_send(amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+        to_address: BIDDER.to_string(),
+        amount: vec![Coin::new(amount, "uluna")],
+    }))
+}
+
+fn cw20_transfer(contract_addr: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: BIDDER.to_string(),
+            amount: Uint128::from(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    }))
+}
+
+#[test]
+fn refunds_are_merged_per_asset() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    submit_cw20_order(&mut deps, 300);
+    submit_order(&mut deps, &uluna(), 500, 500, 50, None, None).unwrap();
+    submit_cw20_order(&mut deps, 200);
+
+    // uluna offers and fees go out in one send, the ASK_TOKEN offers in one transfer
+    let res = cancel_orders(&mut deps, Some(vec![1, 2, 3, 4]), None).unwrap();
+    assert_eq!(res.messages, vec![bank_send(1650), cw20_transfer(ASK_TOKEN, 500)]);
+    assert_eq!(attr_value(&res, "order_ids"), "1,2,3,4");
+    for order_id in 1..=4 {
+        assert_eq!(
+            read_order(&deps.storage, order_id).unwrap_err(),
+            ContractError::OrderNotFound { id: order_id }
+        );
+    }
+
+    // without order_ids every order of the sender is cancelled the same way
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    submit_cw20_order(&mut deps, 300);
+    submit_cw20_order(&mut deps, 200);
+    let res = cancel_orders(&mut deps, None, None).unwrap();
+    assert_eq!(res.messages, vec![bank_send(1100), cw20_transfer(ASK_TOKEN, 500)]);
+}
+
+#[test]
+fn pair_filter_keeps_orders_of_other_pairs() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    deps.querier
+        .with_pair(OTHER_PAIR, [uluna(), token(FEE_TOKEN)], Decimal::percent(120));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RegisterPair {
+            pair_addr: OTHER_PAIR.to_string(),
+            dex: Dex::Terraswap,
+            asset_infos: [uluna(), token(FEE_TOKEN)],
+        },
+    )
+    .unwrap();
+
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[Coin::new(550, "uluna")]),
+        ExecuteMsg::SubmitOrder {
+            pair_addr: OTHER_PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(500u128),
+            },
+            ask_asset: Asset {
+                info: token(FEE_TOKEN),
+                amount: Uint128::from(500u128),
+            },
+            fee_amount: Uint128::from(50u128),
+            recurring: None,
+            expires_at: None,
+            kind: None,
+            route: None,
+            candidate_pools: None,
+        },
+    )
+    .unwrap();
+    submit_order(&mut deps, &uluna(), 200, 200, 20, None, None).unwrap();
+
+    // listed orders on other pairs are left open
+    let res = cancel_orders(&mut deps, Some(vec![1, 2]), Some(PAIR)).unwrap();
+    assert_eq!(res.messages, vec![bank_send(1100)]);
+    assert_eq!(attr_value(&res, "order_ids"), "1");
+    assert!(read_order(&deps.storage, 2).is_ok());
+    assert!(read_order(&deps.storage, 3).is_ok());
+
+    let res = cancel_orders(&mut deps, None, Some(OTHER_PAIR)).unwrap();
+    assert_eq!(res.messages, vec![bank_send(550)]);
+    assert_eq!(attr_value(&res, "order_ids"), "2");
+    assert!(read_order(&deps.storage, 3).is_ok());
+}
@@ -0,0 +1,208 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use terraswap::asset::{Asset, AssetInfo};
+
+use crate::contract::{execute, query};
+use crate::error::ContractError;
+use crate::msg::{Dex, ExecuteMsg, QueryMsg, RequiredFeeResponse};
+use crate::state::{read_order, RecurringOrderOpt};
+use crate::testing::{
+    attr_value, execute_order, settle, setup, submit_order, token, uluna, MockDeps, ASK_TOKEN, BIDDER,
+    EXECUTOR, FEE_TOKEN, OWNER, RESERVE,
+};
+
+fn cw20_transfer(token_addr: &str, recipient: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::from(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    }))
+}
+
+fn fee_payout(fee_token: &AssetInfo, recipient: &str, amount: u128) -> SubMsg {
+    match fee_token {
+        AssetInfo::NativeToken { denom } => SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin::new(amount, denom)],
+        })),
+        AssetInfo::Token { contract_addr } => cw20_transfer(contract_addr, recipient, amount),
+    }
+}
+
+// 1000 uluna asking 1000 ASK_TOKEN settled with 1200, the 200 excess and the 100 fee
+// are split 30% to the executor and 70% to the reserve
+fn assert_executor_reserve_split(fee_token: AssetInfo) {
+    let mut deps = setup(fee_token.clone(), Decimal::percent(120));
+    submit_order(&mut deps, &fee_token, 1000, 1000, 100, None, None).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+    let res = settle(&mut deps, 1200).unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![
+            cw20_transfer(ASK_TOKEN, BIDDER, 1000),
+            cw20_transfer(ASK_TOKEN, EXECUTOR, 60),
+            cw20_transfer(ASK_TOKEN, RESERVE, 140),
+            fee_payout(&fee_token, EXECUTOR, 30),
+            fee_payout(&fee_token, RESERVE, 70),
+        ]
+    );
+    assert_eq!(attr_value(&res, "executor_excess_amount"), "60");
+    assert_eq!(attr_value(&res, "reserve_excess_amount"), "140");
+    assert_eq!(attr_value(&res, "executor_fee_amount"), "30");
+    assert_eq!(attr_value(&res, "reserve_fee_amount"), "70");
+}
+
+#[test]
+fn executor_reserve_split_native_fee_token() {
+    assert_executor_reserve_split(uluna());
+}
+
+#[test]
+fn executor_reserve_split_cw20_fee_token() {
+    assert_executor_reserve_split(token(FEE_TOKEN));
+}
+
+#[test]
+fn recurring_order_releases_one_fee_installment() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let recurring = RecurringOrderOpt {
+        belief_price: Decimal::one(),
+        swapback_belief_price: Decimal::percent(110),
+        total_loop: 2,
+        remaining_loop: 2,
+        hodler: false,
+    };
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, Some(recurring), None).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+    let res = settle(&mut deps, 1200).unwrap();
+
+    // the ask stays in the contract for the loop order, only 100/3 of the fee is paid
+    assert_eq!(
+        res.messages,
+        vec![
+            cw20_transfer(ASK_TOKEN, EXECUTOR, 60),
+            cw20_transfer(ASK_TOKEN, RESERVE, 140),
+            fee_payout(&uluna(), EXECUTOR, 9),
+            fee_payout(&uluna(), RESERVE, 24),
+        ]
+    );
+    assert_eq!(attr_value(&res, "fee_amount"), "33");
+    assert_eq!(attr_value(&res, "executor_fee_amount"), "9");
+    assert_eq!(attr_value(&res, "reserve_fee_amount"), "24");
+
+    // the loop order carries the rest of the fee
+    let loop_order = read_order(&deps.storage, 2).unwrap();
+    assert_eq!(loop_order.fee_amount, Uint128::from(67u128));
+    assert_eq!(loop_order.offer_asset.amount, Uint128::from(1000u128));
+    assert_eq!(loop_order.recurring.unwrap().remaining_loop, 1);
+}
+
+#[test]
+fn fee_token_cannot_change_with_open_orders() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let update_fee_token = |deps: &mut MockDeps| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::UpdateConfig {
+                guardian: None,
+                remove_guardian: None,
+                fee_token: Some(token(FEE_TOKEN)),
+                min_fee_amount: None,
+                min_fee_percent: None,
+                executor_fee_percent: None,
+                reserve_addr: None,
+                terraswap_factory: None,
+            },
+        )
+    };
+
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    assert_eq!(
+        update_fee_token(&mut deps).unwrap_err(),
+        ContractError::InvalidConfig {
+            reason: "fee_token cannot change while orders are open".to_string(),
+        }
+    );
+
+    // once the order is settled the fee token can change
+    execute_order(&mut deps, 1, None).unwrap();
+    settle(&mut deps, 1200).unwrap();
+    update_fee_token(&mut deps).unwrap();
+}
+
+#[test]
+fn required_fee_is_priced_through_a_registered_pair() {
+    let mut deps = setup(token(FEE_TOKEN), Decimal::percent(120));
+    // the factory knows no pair, only the astroport pair prices uluna in the fee token
+    deps.querier.with_pair("astropair0000", [uluna(), token(FEE_TOKEN)], Decimal::percent(50));
+    for msg in [
+        ExecuteMsg::RegisterPair {
+            pair_addr: "astropair0000".to_string(),
+            dex: Dex::Astroport,
+            asset_infos: [token(FEE_TOKEN), uluna()],
+        },
+        ExecuteMsg::UpdateConfig {
+            guardian: None,
+            remove_guardian: None,
+            fee_token: None,
+            min_fee_amount: None,
+            // 1%
+            min_fee_percent: Some(Uint128::from(10000u128)),
+            executor_fee_percent: None,
+            reserve_addr: None,
+            terraswap_factory: None,
+        },
+    ] {
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    }
+
+    let res: RequiredFeeResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequiredFee {
+                offer_asset: Asset {
+                    info: uluna(),
+                    amount: Uint128::from(10000u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    // 1% of 10000 uluna priced at 0.5
+    assert_eq!(res.fee_amount, Uint128::from(50u128));
+}
+
+#[test]
+fn cw20_fee_token_address_is_validated() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateConfig {
+            guardian: None,
+            remove_guardian: None,
+            fee_token: Some(token("FEE0000")),
+            min_fee_amount: None,
+            min_fee_percent: None,
+            executor_fee_percent: None,
+            reserve_addr: None,
+            terraswap_factory: None,
+        },
+    );
+    match res.unwrap_err() {
+        ContractError::Std(_) => {}
+        err => panic!("unexpected error {:?}", err),
+    }
+}
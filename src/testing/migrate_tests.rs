@@ -0,0 +1,96 @@
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{from_slice, Storage, Uint128};
+
+use crate::contract::migrate;
+use crate::msg::{Dex, MigrateMsg};
+use crate::state::{OrderInfo, OrderKind, CONFIG};
+use crate::testing::{mock_querier::mock_dependencies, uluna, OWNER, RESERVE};
+
+#[test]
+fn order_stored_before_migration_deserializes() {
+    // fields of the orders stored by the first deployed version
+    let order: OrderInfo = from_slice(
+        br#"{
+            "order_id": 1,
+            "bidder_addr": "bidder0000",
+            "pair_addr": "pair0000",
+            "offer_asset": {"info": {"native_token": {"denom": "uluna"}}, "amount": "1000"},
+            "ask_asset": {"info": {"token": {"contract_addr": "asset0000"}}, "amount": "1000"},
+            "fee_amount": "100",
+            "recurring": null
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(order.dex, Dex::Terraswap);
+    assert_eq!(order.kind, OrderKind::TakeProfit {});
+    assert_eq!(order.pending_fee_amount, Uint128::zero());
+    assert_eq!(order.filled_offer, Uint128::zero());
+    assert_eq!(order.filled_ask, Uint128::zero());
+    assert_eq!(order.expires_at, None);
+}
+
+#[test]
+fn migrate_config_without_owner() {
+    let mut deps = mock_dependencies(&[]);
+    // config stored by the first deployed version
+    deps.storage.set(
+        b"config",
+        br#"{
+            "fee_token": {"native_token": {"denom": "uluna"}},
+            "min_fee_amount": "10",
+            "min_fee_percent": "1000",
+            "executor_fee_percent": "300000",
+            "reserve_addr": "reserve0000"
+        }"#,
+    );
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: None,
+            terraswap_factory: Some("factory0000".to_string()),
+        },
+    )
+    .unwrap();
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.owner, RESERVE);
+    assert_eq!(config.reserve_addr, RESERVE);
+    assert_eq!(config.fee_token, uluna());
+    assert_eq!(config.terraswap_factory, "factory0000");
+    assert_eq!(config.guardian, None);
+}
+
+#[test]
+fn migrate_config_with_owner() {
+    let mut deps = mock_dependencies(&[]);
+    // config stored before the owner, terraswap_factory already set
+    deps.storage.set(
+        b"config",
+        br#"{
+            "fee_token": {"native_token": {"denom": "uluna"}},
+            "min_fee_amount": "10",
+            "min_fee_percent": "1000",
+            "executor_fee_percent": "300000",
+            "reserve_addr": "reserve0000",
+            "terraswap_factory": "factory0000"
+        }"#,
+    );
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(OWNER.to_string()),
+            terraswap_factory: None,
+        },
+    )
+    .unwrap();
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.owner, OWNER);
+    assert_eq!(config.reserve_addr, RESERVE);
+    assert_eq!(config.terraswap_factory, "factory0000");
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use terraswap::asset::{AssetInfo, PairInfo};
+use terraswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use terraswap::router::{QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse};
+
+/// mock_dependencies with pairs and cw20 balances, native balances are the bank balances
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)])),
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    // pair address -> (asset infos, return per offer)
+    pairs: HashMap<String, ([AssetInfo; 2], Decimal)>,
+    // router address -> return per offer of any route
+    routers: HashMap<String, Decimal>,
+    // token address -> holder -> balance
+    token_balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            pairs: HashMap::new(),
+            routers: HashMap::new(),
+            token_balances: HashMap::new(),
+        }
+    }
+
+    /// pair swapping its assets at price (return per offer) in both directions
+    pub fn with_pair(&mut self, pair_addr: &str, asset_infos: [AssetInfo; 2], price: Decimal) {
+        self.pairs.insert(pair_addr.to_string(), (asset_infos, price));
+    }
+
+    /// router returning price per offer for any route
+    pub fn with_router(&mut self, router_addr: &str, price: Decimal) {
+        self.routers.insert(router_addr.to_string(), price);
+    }
+
+    pub fn with_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
+
+    pub fn add_token_balance(&mut self, token_addr: &str, holder: &str, amount: Uint128) {
+        *self
+            .token_balances
+            .entry(token_addr.to_string())
+            .or_default()
+            .entry(holder.to_string())
+            .or_default() += amount;
+    }
+
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                if let Some((asset_infos, price)) = self.pairs.get(contract_addr) {
+                    return match from_binary(msg) {
+                        Ok(PairQueryMsg::Pair {}) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                            asset_infos: asset_infos.clone(),
+                            contract_addr: contract_addr.clone(),
+                            liquidity_token: "liquidity0000".to_string(),
+                        }))),
+                        Ok(PairQueryMsg::Simulation { offer_asset }) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
+                                return_amount: offer_asset.amount * *price,
+                                spread_amount: Uint128::zero(),
+                                commission_amount: Uint128::zero(),
+                            })))
+                        }
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                            kind: "pair query".to_string(),
+                        }),
+                    };
+                }
+
+                if let Some(price) = self.routers.get(contract_addr) {
+                    return match from_binary(msg) {
+                        Ok(RouterQueryMsg::SimulateSwapOperations { offer_amount, .. }) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&SimulateSwapOperationsResponse {
+                                amount: offer_amount * *price,
+                            })))
+                        }
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                            kind: "router query".to_string(),
+                        }),
+                    };
+                }
+
+                match from_binary(msg) {
+                    Ok(Cw20QueryMsg::Balance { address }) => {
+                        let balance = self
+                            .token_balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse { balance })))
+                    }
+                    _ => SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    }),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+mod cancel_tests;
+mod fee_tests;
+mod migrate_tests;
+mod mock_querier;
+mod order_tests;
+mod owner_tests;
+mod pause_tests;
+mod registry_tests;
+mod settle_tests;
+mod update_tests;
+
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    Coin, ContractResult, Decimal, OwnedDeps, Reply, Response, SubMsgExecutionResponse, Uint128,
+};
+use terraswap::asset::{Asset, AssetInfo};
+
+use crate::contract::{execute, instantiate, reply};
+use crate::error::ContractError;
+use crate::msg::{Dex, ExecuteMsg, InstantiateMsg};
+use crate::state::{OrderKind, RecurringOrderOpt, EXECUTE_ORDER_REPLY_ID};
+use mock_querier::{mock_dependencies, WasmMockQuerier};
+
+pub const OWNER: &str = "owner0000";
+pub const RESERVE: &str = "reserve0000";
+pub const BIDDER: &str = "bidder0000";
+pub const EXECUTOR: &str = "executor0000";
+pub const PAIR: &str = "pair0000";
+pub const ASK_TOKEN: &str = "asset0000";
+pub const FEE_TOKEN: &str = "fee0000";
+
+pub type MockDeps = OwnedDeps<MockStorage, MockApi, WasmMockQuerier>;
+
+pub fn uluna() -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    }
+}
+
+pub fn token(contract_addr: &str) -> AssetInfo {
+    AssetInfo::Token {
+        contract_addr: contract_addr.to_string(),
+    }
+}
+
+/// contract paying 30% of fees and excess to executors, with a registered uluna/ASK_TOKEN pair
+/// returning price ASK_TOKEN per uluna
+pub fn setup(fee_token: AssetInfo, price: Decimal) -> MockDeps {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_pair(PAIR, [uluna(), token(ASK_TOKEN)], price);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            owner: OWNER.to_string(),
+            guardian: None,
+            fee_token,
+            min_fee_amount: Uint128::from(10u128),
+            min_fee_percent: Uint128::zero(),
+            executor_fee_percent: Uint128::from(300000u128),
+            reserve_addr: RESERVE.to_string(),
+            terraswap_factory: "factory0000".to_string(),
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RegisterPair {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            asset_infos: [uluna(), token(ASK_TOKEN)],
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+/// BIDDER offers offer_amount uluna for ask_amount ASK_TOKEN, paying fee_amount in the fee token
+pub fn submit_order(
+    deps: &mut MockDeps,
+    fee_token: &AssetInfo,
+    offer_amount: u128,
+    ask_amount: u128,
+    fee_amount: u128,
+    recurring: Option<RecurringOrderOpt>,
+    kind: Option<OrderKind>,
+) -> Result<Response, ContractError> {
+    let funds = if *fee_token == uluna() {
+        vec![Coin::new(offer_amount + fee_amount, "uluna")]
+    } else {
+        vec![Coin::new(offer_amount, "uluna")]
+    };
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &funds),
+        ExecuteMsg::SubmitOrder {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(offer_amount),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(ask_amount),
+            },
+            fee_amount: Uint128::from(fee_amount),
+            recurring,
+            expires_at: None,
+            kind,
+            route: None,
+            candidate_pools: None,
+        },
+    )
+}
+
+pub fn execute_order(deps: &mut MockDeps, order_id: u64, amount: Option<u128>) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(EXECUTOR, &[]),
+        ExecuteMsg::ExecuteOrder {
+            order_id,
+            amount: amount.map(Uint128::from),
+            split: None,
+        },
+    )
+}
+
+/// the pending swap returns return_amount ASK_TOKEN to the contract, then the reply settles it
+pub fn settle(deps: &mut MockDeps, return_amount: u128) -> Result<Response, ContractError> {
+    deps.querier
+        .add_token_balance(ASK_TOKEN, MOCK_CONTRACT_ADDR, Uint128::from(return_amount));
+
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: EXECUTE_ORDER_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+}
+
+pub fn attr_value(res: &Response, key: &str) -> String {
+    res.attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+        .unwrap_or_else(|| panic!("missing attribute {}", key))
+}
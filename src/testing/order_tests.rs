@@ -0,0 +1,97 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
+use terraswap::asset::Asset;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::{Cw20HookMsg, Dex, ExecuteMsg};
+use crate::state::read_order;
+use crate::testing::{setup, token, uluna, ASK_TOKEN, BIDDER, EXECUTOR, FEE_TOKEN, PAIR};
+
+#[test]
+fn cw20_submission_sending_only_the_fee_is_rejected() {
+    let mut deps = setup(token(FEE_TOKEN), Decimal::percent(120));
+
+    // the sent fee token amount only covers the fee, leaving a zero offer
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FEE_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: BIDDER.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::SubmitOrder {
+                pair_addr: PAIR.to_string(),
+                dex: Dex::Terraswap,
+                ask_asset: Asset {
+                    info: token(ASK_TOKEN),
+                    amount: Uint128::from(1000u128),
+                },
+                fee_amount: Uint128::from(100u128),
+                recurring: None,
+                expires_at: None,
+                kind: None,
+                route: None,
+                candidate_pools: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidOrder {
+            reason: "offer_asset amount should be greater than 0".to_string(),
+        }
+    );
+}
+
+#[test]
+fn repeated_expired_order_is_refunded_once() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(BIDDER, &[Coin::new(1100, "uluna")]),
+        ExecuteMsg::SubmitOrder {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(1000u128),
+            },
+            fee_amount: Uint128::from(100u128),
+            recurring: None,
+            expires_at: Some(Expiration::AtHeight(env.block.height + 1)),
+            kind: None,
+            route: None,
+            candidate_pools: None,
+        },
+    )
+    .unwrap();
+
+    let mut expired_env = mock_env();
+    expired_env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        expired_env,
+        mock_info(EXECUTOR, &[]),
+        ExecuteMsg::RefundExpired { order_ids: vec![1, 1] },
+    )
+    .unwrap();
+
+    let refund = |amount: u128| {
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: BIDDER.to_string(),
+            amount: vec![Coin::new(amount, "uluna")],
+        }))
+    };
+    assert_eq!(res.messages, vec![refund(1000), refund(100)]);
+    assert!(read_order(&deps.storage, 1).is_err());
+}
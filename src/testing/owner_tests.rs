@@ -0,0 +1,92 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Decimal, Response, Uint128};
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::ExecuteMsg;
+use crate::state::CONFIG;
+use crate::testing::{setup, uluna, MockDeps, BIDDER, OWNER, RESERVE};
+
+const NEW_OWNER: &str = "newowner0000";
+
+fn execute_as(deps: &mut MockDeps, sender: &str, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+}
+
+fn update_min_fee(deps: &mut MockDeps, sender: &str) -> Result<Response, ContractError> {
+    execute_as(
+        deps,
+        sender,
+        ExecuteMsg::UpdateConfig {
+            guardian: None,
+            remove_guardian: None,
+            fee_token: None,
+            min_fee_amount: Some(Uint128::from(20u128)),
+            min_fee_percent: None,
+            executor_fee_percent: None,
+            reserve_addr: None,
+            terraswap_factory: None,
+        },
+    )
+}
+
+fn propose(deps: &mut MockDeps, sender: &str) -> Result<Response, ContractError> {
+    execute_as(
+        deps,
+        sender,
+        ExecuteMsg::ProposeNewOwner {
+            owner: NEW_OWNER.to_string(),
+        },
+    )
+}
+
+#[test]
+fn ownership_is_transferred_once_accepted() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+
+    // reserve_addr no longer administers the contract
+    assert_eq!(update_min_fee(&mut deps, RESERVE).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(propose(&mut deps, RESERVE).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(
+        execute_as(&mut deps, NEW_OWNER, ExecuteMsg::AcceptOwnership {}).unwrap_err(),
+        ContractError::NoOwnershipProposal {}
+    );
+
+    propose(&mut deps, OWNER).unwrap();
+    assert_eq!(
+        execute_as(&mut deps, BIDDER, ExecuteMsg::AcceptOwnership {}).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    // the owner keeps its rights until the proposal is accepted
+    update_min_fee(&mut deps, OWNER).unwrap();
+
+    execute_as(&mut deps, NEW_OWNER, ExecuteMsg::AcceptOwnership {}).unwrap();
+    assert_eq!(CONFIG.load(&deps.storage).unwrap().owner, NEW_OWNER);
+    assert_eq!(update_min_fee(&mut deps, OWNER).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(propose(&mut deps, OWNER).unwrap_err(), ContractError::Unauthorized {});
+    update_min_fee(&mut deps, NEW_OWNER).unwrap();
+
+    // the proposal is consumed
+    assert_eq!(
+        execute_as(&mut deps, NEW_OWNER, ExecuteMsg::AcceptOwnership {}).unwrap_err(),
+        ContractError::NoOwnershipProposal {}
+    );
+}
+
+#[test]
+fn dropped_proposal_cannot_be_accepted() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    propose(&mut deps, OWNER).unwrap();
+
+    assert_eq!(
+        execute_as(&mut deps, NEW_OWNER, ExecuteMsg::DropOwnershipProposal {}).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    execute_as(&mut deps, OWNER, ExecuteMsg::DropOwnershipProposal {}).unwrap();
+
+    assert_eq!(
+        execute_as(&mut deps, NEW_OWNER, ExecuteMsg::AcceptOwnership {}).unwrap_err(),
+        ContractError::NoOwnershipProposal {}
+    );
+    assert_eq!(CONFIG.load(&deps.storage).unwrap().owner, OWNER);
+}
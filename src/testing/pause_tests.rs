@@ -0,0 +1,177 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Coin, Decimal, Response, Uint128};
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::{Dex, ExecuteMsg};
+use crate::state::read_order;
+use crate::testing::{execute_order, settle, setup, submit_order, uluna, MockDeps, BIDDER, OWNER};
+
+const GUARDIAN: &str = "guardian0000";
+
+fn set_pause(
+    deps: &mut MockDeps,
+    sender: &str,
+    submit: Option<bool>,
+    execute_paused: Option<bool>,
+    paused_dexes: Option<Vec<Dex>>,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(sender, &[]),
+        ExecuteMsg::SetPause {
+            submit,
+            execute: execute_paused,
+            paused_dexes,
+        },
+    )
+}
+
+fn update_order(
+    deps: &mut MockDeps,
+    funds: &[Coin],
+    add_offer: Option<u128>,
+    withdraw_offer: Option<u128>,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, funds),
+        ExecuteMsg::UpdateOrder {
+            order_id: 1,
+            ask_amount: None,
+            add_offer: add_offer.map(Uint128::from),
+            withdraw_offer: withdraw_offer.map(Uint128::from),
+            add_fee: None,
+        },
+    )
+}
+
+fn paused(operation: &str) -> ContractError {
+    ContractError::Paused {
+        operation: operation.to_string(),
+    }
+}
+
+#[test]
+fn only_owner_or_guardian_can_pause() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateConfig {
+            guardian: Some(GUARDIAN.to_string()),
+            remove_guardian: None,
+            fee_token: None,
+            min_fee_amount: None,
+            min_fee_percent: None,
+            executor_fee_percent: None,
+            reserve_addr: None,
+            terraswap_factory: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        set_pause(&mut deps, BIDDER, Some(true), None, None).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    set_pause(&mut deps, GUARDIAN, Some(true), None, None).unwrap();
+    set_pause(&mut deps, OWNER, Some(false), None, None).unwrap();
+}
+
+#[test]
+fn paused_submissions_and_executions() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+
+    set_pause(&mut deps, OWNER, Some(true), Some(true), None).unwrap();
+    assert_eq!(
+        submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap_err(),
+        paused("order submission")
+    );
+    assert_eq!(execute_order(&mut deps, 1, None).unwrap_err(), paused("execution"));
+
+    // open orders take no new funds but can still be withdrawn from
+    assert_eq!(
+        update_order(&mut deps, &[Coin::new(500, "uluna")], Some(500), None).unwrap_err(),
+        paused("order submission")
+    );
+    update_order(&mut deps, &[], None, Some(400)).unwrap();
+    assert_eq!(read_order(&deps.storage, 1).unwrap().offer_asset.amount, Uint128::from(600u128));
+
+    // submissions resume while executions stay paused
+    set_pause(&mut deps, OWNER, Some(false), None, None).unwrap();
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    assert_eq!(execute_order(&mut deps, 2, None).unwrap_err(), paused("execution"));
+}
+
+#[test]
+fn paused_dex_stops_its_executions() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+
+    set_pause(&mut deps, OWNER, None, None, Some(vec![Dex::Astroport])).unwrap();
+    execute_order(&mut deps, 1, Some(500)).unwrap();
+    settle(&mut deps, 600).unwrap();
+
+    set_pause(&mut deps, OWNER, None, None, Some(vec![Dex::Terraswap])).unwrap();
+    assert_eq!(
+        execute_order(&mut deps, 1, Some(500)).unwrap_err(),
+        paused("execution on terraswap")
+    );
+}
+
+#[test]
+fn orders_can_be_cancelled_while_paused() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+
+    set_pause(&mut deps, OWNER, Some(true), Some(true), Some(vec![Dex::Terraswap])).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[]),
+        ExecuteMsg::CancelOrder { order_id: 1 },
+    )
+    .unwrap();
+    assert!(read_order(&deps.storage, 1).is_err());
+}
+
+#[test]
+fn removed_guardian_cannot_pause() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let update_guardian = |deps: &mut MockDeps, guardian: Option<String>, remove_guardian: Option<bool>| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::UpdateConfig {
+                guardian,
+                remove_guardian,
+                fee_token: None,
+                min_fee_amount: None,
+                min_fee_percent: None,
+                executor_fee_percent: None,
+                reserve_addr: None,
+                terraswap_factory: None,
+            },
+        )
+    };
+
+    update_guardian(&mut deps, Some(GUARDIAN.to_string()), None).unwrap();
+    assert_eq!(
+        update_guardian(&mut deps, Some(GUARDIAN.to_string()), Some(true)).unwrap_err(),
+        ContractError::InvalidConfig {
+            reason: "cannot set and remove the guardian at once".to_string(),
+        }
+    );
+    update_guardian(&mut deps, None, Some(true)).unwrap();
+
+    assert_eq!(
+        set_pause(&mut deps, GUARDIAN, Some(true), None, None).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+}
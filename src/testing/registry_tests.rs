@@ -0,0 +1,209 @@
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Addr, Coin, CosmosMsg, Decimal, Response, Uint128, WasmMsg};
+use terraswap::asset::Asset;
+use terraswap::router::SwapOperation;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::{Dex, ExecuteMsg};
+use crate::state::Pool;
+use crate::testing::{attr_value, execute_order, setup, submit_order, token, uluna, MockDeps, ASK_TOKEN, BIDDER, OWNER, PAIR};
+
+const ROUTER: &str = "router0000";
+
+fn submit_route_order(deps: &mut MockDeps, router_addr: &str) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[Coin::new(1100, "uluna")]),
+        ExecuteMsg::SubmitOrder {
+            pair_addr: router_addr.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(1000u128),
+            },
+            fee_amount: Uint128::from(100u128),
+            recurring: None,
+            expires_at: None,
+            kind: None,
+            route: Some(vec![SwapOperation::TerraSwap {
+                offer_asset_info: uluna(),
+                ask_asset_info: token(ASK_TOKEN),
+            }]),
+            candidate_pools: None,
+        },
+    )
+}
+
+#[test]
+fn route_order_requires_registered_router() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+
+    assert_eq!(
+        submit_route_order(&mut deps, "fakerouter0000").unwrap_err(),
+        ContractError::RouterNotRegistered {
+            router_addr: "fakerouter0000".to_string(),
+        }
+    );
+
+    // a router of another dex is rejected as well
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RegisterRouter {
+            router_addr: ROUTER.to_string(),
+            dex: Dex::Astroport,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        submit_route_order(&mut deps, ROUTER).unwrap_err(),
+        ContractError::RouterNotRegistered {
+            router_addr: ROUTER.to_string(),
+        }
+    );
+}
+
+#[test]
+fn route_hops_are_looked_up_by_asset_pair() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    deps.querier.with_router(ROUTER, Decimal::percent(120));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RegisterRouter {
+            router_addr: ROUTER.to_string(),
+            dex: Dex::Terraswap,
+        },
+    )
+    .unwrap();
+
+    submit_route_order(&mut deps, ROUTER).unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::DeregisterPair {
+            pair_addr: PAIR.to_string(),
+        },
+    )
+    .unwrap();
+    match submit_route_order(&mut deps, ROUTER).unwrap_err() {
+        ContractError::InvalidRoute { .. } => {}
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+fn owner_execute(deps: &mut MockDeps, msg: ExecuteMsg) {
+    execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+}
+
+#[test]
+fn order_on_a_deregistered_pair_does_not_execute() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+
+    owner_execute(
+        &mut deps,
+        ExecuteMsg::DeregisterPair {
+            pair_addr: PAIR.to_string(),
+        },
+    );
+    assert_eq!(
+        execute_order(&mut deps, 1, None).unwrap_err(),
+        ContractError::PairNotRegistered {
+            pair_addr: PAIR.to_string(),
+        }
+    );
+}
+
+#[test]
+fn deregistered_candidate_pools_are_left_out() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    // the better candidate is deregistered after the submission
+    deps.querier
+        .with_pair("pair0001", [uluna(), token(ASK_TOKEN)], Decimal::percent(150));
+    owner_execute(
+        &mut deps,
+        ExecuteMsg::RegisterPair {
+            pair_addr: "pair0001".to_string(),
+            dex: Dex::Astroport,
+            asset_infos: [uluna(), token(ASK_TOKEN)],
+        },
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[Coin::new(1100, "uluna")]),
+        ExecuteMsg::SubmitOrder {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(1000u128),
+            },
+            fee_amount: Uint128::from(100u128),
+            recurring: None,
+            expires_at: None,
+            kind: None,
+            route: None,
+            candidate_pools: Some(vec![Pool {
+                pair_addr: Addr::unchecked("pair0001"),
+                dex: Dex::Astroport,
+            }]),
+        },
+    )
+    .unwrap();
+    owner_execute(
+        &mut deps,
+        ExecuteMsg::DeregisterPair {
+            pair_addr: "pair0001".to_string(),
+        },
+    );
+
+    let res = execute_order(&mut deps, 1, None).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => assert_eq!(contract_addr, PAIR),
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    assert_eq!(attr_value(&res, "pair_addr"), PAIR);
+}
+
+#[test]
+fn route_order_through_a_deregistered_router_does_not_execute() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    deps.querier.with_router(ROUTER, Decimal::percent(120));
+    owner_execute(
+        &mut deps,
+        ExecuteMsg::RegisterRouter {
+            router_addr: ROUTER.to_string(),
+            dex: Dex::Terraswap,
+        },
+    );
+    submit_route_order(&mut deps, ROUTER).unwrap();
+
+    owner_execute(
+        &mut deps,
+        ExecuteMsg::DeregisterRouter {
+            router_addr: ROUTER.to_string(),
+        },
+    );
+    assert_eq!(
+        execute_order(&mut deps, 1, None).unwrap_err(),
+        ContractError::RouterNotRegistered {
+            router_addr: ROUTER.to_string(),
+        }
+    );
+}
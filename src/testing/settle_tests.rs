@@ -0,0 +1,277 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply, Response, SubMsg,
+    SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use terraswap::asset::Asset;
+
+use crate::contract::{execute, query, reply};
+use crate::error::ContractError;
+use crate::msg::{Dex, ExecuteMsg, HodlerGainResponse, QueryMsg};
+use crate::state::{read_order, OrderKind, RecurringOrderOpt, EXECUTE_ORDER_REPLY_ID};
+use crate::testing::{
+    attr_value, execute_order, settle, setup, submit_order, token, uluna, MockDeps, ASK_TOKEN, BIDDER,
+    EXECUTOR, PAIR, RESERVE,
+};
+
+fn bank_send(recipient: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: vec![Coin::new(amount, "uluna")],
+    }))
+}
+
+fn cw20_transfer(recipient: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: ASK_TOKEN.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::from(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    }))
+}
+
+// take-profit 1000 ASK_TOKEN and stop-loss triggered below 0.9 sharing 1000 uluna and a 100 uluna fee
+fn submit_oco(deps: &mut MockDeps) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[Coin::new(1100, "uluna")]),
+        ExecuteMsg::SubmitOco {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(1000u128),
+            },
+            trigger_price: Decimal::percent(90),
+            min_return: Uint128::from(800u128),
+            fee_amount: Uint128::from(100u128),
+            expires_at: None,
+        },
+    )
+    .unwrap();
+}
+
+fn cancel_order(deps: &mut MockDeps, order_id: u64) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[]),
+        ExecuteMsg::CancelOrder { order_id },
+    )
+}
+
+#[test]
+fn settlement_reverts_below_ask() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+
+    // the swap returned less than simulated
+    let err = settle(&mut deps, 900).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientReturn {
+            expected: Uint128::from(1000u128),
+            simulated: Uint128::from(900u128),
+        }
+    );
+}
+
+#[test]
+fn partial_fill_pro_rates_ask_and_fee() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+    execute_order(&mut deps, 1, Some(400)).unwrap();
+    let res = settle(&mut deps, 480).unwrap();
+
+    // 400 of the 1000 offer fills 400 of the ask and 40 of the fee
+    assert_eq!(
+        res.messages,
+        vec![
+            cw20_transfer(BIDDER, 400),
+            cw20_transfer(EXECUTOR, 24),
+            cw20_transfer(RESERVE, 56),
+            bank_send(EXECUTOR, 12),
+            bank_send(RESERVE, 28),
+        ]
+    );
+    assert_eq!(attr_value(&res, "partial_fill"), "true");
+    assert_eq!(attr_value(&res, "fee_amount"), "40");
+
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(600u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(600u128));
+    assert_eq!(order.fee_amount, Uint128::from(60u128));
+    assert_eq!(order.filled_offer, Uint128::from(400u128));
+    assert_eq!(order.filled_ask, Uint128::from(400u128));
+}
+
+#[test]
+fn oco_fill_consumes_escrow_once() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_oco(&mut deps);
+
+    execute_order(&mut deps, 1, None).unwrap();
+    let res = settle(&mut deps, 1200).unwrap();
+    assert_eq!(attr_value(&res, "linked_order_id"), "2");
+    assert_eq!(res.messages[0], cw20_transfer(BIDDER, 1000));
+
+    // the stop-loss sibling is gone with the swapped escrow
+    assert_eq!(
+        read_order(&deps.storage, 2).unwrap_err(),
+        ContractError::OrderNotFound { id: 2 }
+    );
+    assert_eq!(
+        cancel_order(&mut deps, 2).unwrap_err(),
+        ContractError::OrderNotFound { id: 2 }
+    );
+    assert_eq!(
+        execute_order(&mut deps, 2, None).unwrap_err(),
+        ContractError::OrderNotFound { id: 2 }
+    );
+}
+
+#[test]
+fn oco_cancel_refunds_escrow_once() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_oco(&mut deps);
+
+    let res = cancel_order(&mut deps, 2).unwrap();
+    assert_eq!(res.messages, vec![bank_send(BIDDER, 1000), bank_send(BIDDER, 100)]);
+    assert_eq!(
+        cancel_order(&mut deps, 1).unwrap_err(),
+        ContractError::OrderNotFound { id: 1 }
+    );
+
+    // cancelling both siblings at once refunds the shared escrow once
+    submit_oco(&mut deps);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[]),
+        ExecuteMsg::CancelOrders {
+            order_ids: Some(vec![3, 4]),
+            pair_addr: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![bank_send(BIDDER, 1100)]);
+}
+
+#[test]
+fn dca_interval_overflow_errors() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let kind = OrderKind::Dca {
+        interval: u64::MAX,
+        tranches: 2,
+        per_tranche_min_return: Uint128::from(100u128),
+    };
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, Some(kind)).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+    settle(&mut deps, 600).unwrap();
+
+    // the next tranche time does not fit in u64
+    let err = execute_order(&mut deps, 1, None).unwrap_err();
+    assert!(matches!(err, ContractError::Overflow(_)));
+}
+
+#[test]
+fn hodler_zero_swapback_belief_price_is_rejected() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let recurring = RecurringOrderOpt {
+        belief_price: Decimal::one(),
+        swapback_belief_price: Decimal::zero(),
+        total_loop: 2,
+        remaining_loop: 2,
+        hodler: true,
+    };
+
+    let err = submit_order(&mut deps, &uluna(), 1000, 1000, 100, Some(recurring), None).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidOrder {
+            reason: "belief_price and swapback_belief_price should be greater than 0".to_string(),
+        }
+    );
+}
+
+#[test]
+fn surfing_band_percent_is_capped() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    let surfing = |band_percent: u128| {
+        Some(OrderKind::Surfing {
+            band_percent: Uint128::from(band_percent),
+            remaining_loop: 2,
+        })
+    };
+
+    let err = submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, surfing(u128::MAX)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidOrder {
+            reason: "band_percent should be between 0 and 1000000".to_string(),
+        }
+    );
+
+    // 10% band: the re-entry offers the whole return back asking 10% more than the filled offer
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, surfing(100000)).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+    settle(&mut deps, 1200).unwrap();
+
+    let re_entry = read_order(&deps.storage, 2).unwrap();
+    assert_eq!(re_entry.offer_asset.info, token(ASK_TOKEN));
+    assert_eq!(re_entry.ask_asset.info, uluna());
+    assert_eq!(re_entry.ask_asset.amount, Uint128::from(1100u128));
+}
+
+#[test]
+fn hodler_gain_is_kept_by_the_submitted_order_id() {
+    let mut deps = setup(uluna(), Decimal::percent(130));
+    // sell 1000 uluna for 1000 ASK_TOKEN, then buy back 1250 uluna and stop
+    let recurring = RecurringOrderOpt {
+        belief_price: Decimal::one(),
+        swapback_belief_price: Decimal::percent(80),
+        total_loop: 1,
+        remaining_loop: 1,
+        hodler: true,
+    };
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, Some(recurring), None).unwrap();
+    execute_order(&mut deps, 1, None).unwrap();
+    settle(&mut deps, 1300).unwrap();
+
+    let buy_back = read_order(&deps.storage, 2).unwrap();
+    assert_eq!(buy_back.ask_asset.amount, Uint128::from(1250u128));
+    execute_order(&mut deps, 2, None).unwrap();
+    deps.querier
+        .with_balance(MOCK_CONTRACT_ADDR, vec![Coin::new(1300, "uluna")]);
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: EXECUTE_ORDER_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    // the last buy back pays the whole ask to the bidder
+    assert_eq!(res.messages[0], bank_send(BIDDER, 1250));
+    assert!(read_order(&deps.storage, 2).is_err());
+
+    let gain: HodlerGainResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::HodlerGain { order_id: 1 }).unwrap()).unwrap();
+    assert_eq!(gain.base_asset, uluna());
+    assert_eq!(gain.base_gain, Uint128::from(250u128));
+}
@@ -0,0 +1,208 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{to_binary, BankMsg, Coin, CosmosMsg, Decimal, Response, SubMsg, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use terraswap::asset::Asset;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::msg::{Cw20HookMsg, Dex, ExecuteMsg};
+use crate::state::read_order;
+use crate::testing::{setup, submit_order, token, uluna, MockDeps, ASK_TOKEN, BIDDER, FEE_TOKEN, PAIR};
+
+fn update_order(
+    deps: &mut MockDeps,
+    funds: &[Coin],
+    ask_amount: Option<u128>,
+    add_offer: Option<u128>,
+    withdraw_offer: Option<u128>,
+    add_fee: Option<u128>,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, funds),
+        ExecuteMsg::UpdateOrder {
+            order_id: 1,
+            ask_amount: ask_amount.map(Uint128::from),
+            add_offer: add_offer.map(Uint128::from),
+            withdraw_offer: withdraw_offer.map(Uint128::from),
+            add_fee: add_fee.map(Uint128::from),
+        },
+    )
+}
+
+fn cw20_msg(contract_addr: &str, msg: Cw20ExecuteMsg) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&msg).unwrap(),
+        funds: vec![],
+    }))
+}
+
+fn transfer_from(contract_addr: &str, amount: u128) -> SubMsg {
+    cw20_msg(
+        contract_addr,
+        Cw20ExecuteMsg::TransferFrom {
+            owner: BIDDER.to_string(),
+            recipient: MOCK_CONTRACT_ADDR.to_string(),
+            amount: Uint128::from(amount),
+        },
+    )
+}
+
+// BIDDER sends 1100 ASK_TOKEN, offering 1000 ASK_TOKEN for 1000 uluna with the 100 fee included
+fn submit_cw20_order(deps: &mut MockDeps) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ASK_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: BIDDER.to_string(),
+            amount: Uint128::from(1100u128),
+            msg: to_binary(&Cw20HookMsg::SubmitOrder {
+                pair_addr: PAIR.to_string(),
+                dex: Dex::Terraswap,
+                ask_asset: Asset {
+                    info: uluna(),
+                    amount: Uint128::from(1000u128),
+                },
+                fee_amount: Uint128::from(100u128),
+                recurring: None,
+                expires_at: None,
+                kind: None,
+                route: None,
+                candidate_pools: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+}
+
+#[test]
+fn native_offer_is_added_and_withdrawn() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    submit_order(&mut deps, &uluna(), 1000, 1000, 100, None, None).unwrap();
+
+    // the sent funds must match the added offer and fee
+    let err = update_order(&mut deps, &[Coin::new(500, "uluna")], None, Some(500), None, Some(50)).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+
+    let res = update_order(&mut deps, &[Coin::new(550, "uluna")], None, Some(500), None, Some(50)).unwrap();
+    assert!(res.messages.is_empty());
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1500u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(1500u128));
+    assert_eq!(order.fee_amount, Uint128::from(150u128));
+
+    // the withdrawn offer is refunded and the ask follows it down
+    let res = update_order(&mut deps, &[], None, None, Some(300), None).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: BIDDER.to_string(),
+            amount: vec![Coin::new(300, "uluna")],
+        }))]
+    );
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1200u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(1200u128));
+    assert_eq!(order.fee_amount, Uint128::from(150u128));
+
+    // a given ask_amount replaces the rescaled one
+    update_order(&mut deps, &[], Some(1300), None, Some(200), None).unwrap();
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1000u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(1300u128));
+
+    // the whole offer is withdrawn by cancelling
+    let err = update_order(&mut deps, &[], None, None, Some(1000), None).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidOrder {
+            reason: "withdraw_offer should be less than the offer amount, cancel the order instead".to_string(),
+        }
+    );
+}
+
+#[test]
+fn cw20_offer_is_added_and_withdrawn() {
+    let mut deps = setup(token(ASK_TOKEN), Decimal::percent(120));
+    submit_cw20_order(&mut deps);
+
+    // the added offer and fee are pulled in one allowance transfer
+    let res = update_order(&mut deps, &[], None, Some(500), None, Some(50)).unwrap();
+    assert_eq!(res.messages, vec![transfer_from(ASK_TOKEN, 550)]);
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1500u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(1500u128));
+    assert_eq!(order.fee_amount, Uint128::from(150u128));
+
+    let res = update_order(&mut deps, &[], None, None, Some(300), None).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![cw20_msg(
+            ASK_TOKEN,
+            Cw20ExecuteMsg::Transfer {
+                recipient: BIDDER.to_string(),
+                amount: Uint128::from(300u128),
+            }
+        )]
+    );
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1200u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(1200u128));
+}
+
+#[test]
+fn cw20_fee_is_added_apart_from_native_offer() {
+    let mut deps = setup(token(FEE_TOKEN), Decimal::percent(120));
+    submit_order(&mut deps, &token(FEE_TOKEN), 1000, 1000, 100, None, None).unwrap();
+
+    let res = update_order(&mut deps, &[Coin::new(500, "uluna")], None, Some(500), None, Some(50)).unwrap();
+    assert_eq!(res.messages, vec![transfer_from(FEE_TOKEN, 50)]);
+    let order = read_order(&deps.storage, 1).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(1500u128));
+    assert_eq!(order.fee_amount, Uint128::from(150u128));
+}
+
+#[test]
+fn oco_offer_cannot_be_changed() {
+    let mut deps = setup(uluna(), Decimal::percent(120));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(BIDDER, &[Coin::new(1100, "uluna")]),
+        ExecuteMsg::SubmitOco {
+            pair_addr: PAIR.to_string(),
+            dex: Dex::Terraswap,
+            offer_asset: Asset {
+                info: uluna(),
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset: Asset {
+                info: token(ASK_TOKEN),
+                amount: Uint128::from(1000u128),
+            },
+            trigger_price: Decimal::percent(90),
+            min_return: Uint128::from(800u128),
+            fee_amount: Uint128::from(100u128),
+            expires_at: None,
+        },
+    )
+    .unwrap();
+
+    let oco_locked = ContractError::InvalidOrder {
+        reason: "offer of oco orders cannot be changed".to_string(),
+    };
+    assert_eq!(
+        update_order(&mut deps, &[Coin::new(500, "uluna")], None, Some(500), None, None).unwrap_err(),
+        oco_locked
+    );
+    assert_eq!(update_order(&mut deps, &[], None, None, Some(300), None).unwrap_err(), oco_locked);
+
+    // the shared fee can still be raised on both siblings
+    update_order(&mut deps, &[Coin::new(50, "uluna")], None, None, None, Some(50)).unwrap();
+    assert_eq!(read_order(&deps.storage, 1).unwrap().fee_amount, Uint128::from(150u128));
+    assert_eq!(read_order(&deps.storage, 2).unwrap().fee_amount, Uint128::from(150u128));
+}